@@ -0,0 +1,332 @@
+/*!
+ * A compact, versioned binary encoding for spets over u64s.
+ *
+ * The encoding is:
+ *
+ * ```text
+ * magic    b"SPET"
+ * version  1 byte (currently 1)
+ * count    varint
+ * spans    count pairs of varints: (start - previous end, end - start)
+ * ```
+ *
+ * where "previous end" is 0 for the first span and varints are unsigned
+ * LEB128. Since a spet's spans are sorted and don't overlap, both numbers in a
+ * pair are never negative, and for spans that are close together both tend to
+ * fit in a byte or two.
+ */
+
+use std::fmt::{self, Display};
+use std::io::{self, Read, Write};
+use crate::span::CreatableSpan;
+use crate::vecspet::VecSpet;
+
+
+pub const MAGIC: [u8; 4] = *b"SPET";
+pub const VERSION: u8 = 1;
+
+// Enough bytes to hold any u64 at 7 bits a byte.
+const MAX_VARINT_LEN: usize = 10;
+
+
+/**
+ * Error from decoding a spet.
+ */
+#[derive(Debug)]
+pub enum DecodeError {
+    Io(io::Error),
+
+    // The input ended partway through the spet.
+    Truncated,
+
+    // The input doesn't start with MAGIC.
+    BadMagic,
+
+    // The input was written by a version of the format this can't read.
+    UnsupportedVersion(u8),
+
+    // A varint was longer than any u64 needs.
+    VarintTooLong,
+
+    // A span's start or end didn't fit in a u64.
+    Overflow,
+}
+
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Io(err) => err.fmt(f),
+            DecodeError::Truncated => write!(f, "input ended unexpectedly"),
+            DecodeError::BadMagic => write!(f, "not an encoded spet"),
+            DecodeError::UnsupportedVersion(version) =>
+                write!(f, "unsupported version {}", version),
+            DecodeError::VarintTooLong => write!(f, "varint is too long"),
+            DecodeError::Overflow => write!(f, "span bound overflows a u64"),
+        }
+    }
+}
+
+
+impl std::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DecodeError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+
+impl From<io::Error> for DecodeError {
+    fn from(err: io::Error) -> Self {
+        if err.kind() == io::ErrorKind::UnexpectedEof {
+            DecodeError::Truncated
+        } else {
+            DecodeError::Io(err)
+        }
+    }
+}
+
+
+impl<S: CreatableSpan<Domain=u64>> VecSpet<S> {
+    /**
+     * Writes the spet in the encoding described in the binary module.
+     */
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        write_varint(&mut writer, self.spans.len() as u64)?;
+
+        let mut previous_end = 0;
+        for span in &self.spans {
+            write_varint(&mut writer, *span.start() - previous_end)?;
+            write_varint(&mut writer, *span.end() - *span.start())?;
+            previous_end = *span.end();
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Reads a spet written by write_to(). Nothing past the end of the spet is
+     * read, so several spets can be read back-to-back from one reader.
+     */
+    pub fn read_from<R: Read>(mut reader: R) -> Result<Self, DecodeError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(DecodeError::BadMagic);
+        }
+
+        let mut version = [0];
+        reader.read_exact(&mut version)?;
+        if version[0] != VERSION {
+            return Err(DecodeError::UnsupportedVersion(version[0]));
+        }
+
+        let count = read_varint(&mut reader)?;
+
+        // The count isn't trusted for the allocation, so that a corrupt count
+        // fails with Truncated instead of exhausting memory.
+        let mut spans = Vec::with_capacity(count.min(1024) as usize);
+        let mut previous_end: u64 = 0;
+        for _ in 0..count {
+            let start = previous_end.checked_add(read_varint(&mut reader)?)
+                .ok_or(DecodeError::Overflow)?;
+            let end = start.checked_add(read_varint(&mut reader)?)
+                .ok_or(DecodeError::Overflow)?;
+            spans.push(S::new(start, end));
+            previous_end = end;
+        }
+
+        Ok(VecSpet { spans })
+    }
+}
+
+
+fn write_varint<W: Write>(writer: &mut W, mut value: u64) -> io::Result<()> {
+    let mut buffer = [0; MAX_VARINT_LEN];
+    let mut len = 0;
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buffer[len] = byte;
+            len += 1;
+            break;
+        }
+        buffer[len] = byte | 0x80;
+        len += 1;
+    }
+    writer.write_all(&buffer[..len])
+}
+
+
+fn read_varint<R: Read>(reader: &mut R) -> Result<u64, DecodeError> {
+    let mut value: u64 = 0;
+    for i in 0..MAX_VARINT_LEN {
+        let mut byte = [0];
+        reader.read_exact(&mut byte)?;
+
+        let bits = u64::from(byte[0] & 0x7f);
+        let shift = 7 * i as u32;
+        if shift == 63 && bits > 1 {
+            return Err(DecodeError::Overflow);
+        }
+        value |= bits << shift;
+
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(DecodeError::VarintTooLong)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::binary::{MAGIC, VERSION};
+    use crate::span::{SimpleSpan, CreatableSpan};
+    use crate::vecspet::VecSpet;
+
+    type SSpan = SimpleSpan<u64>;
+    type Spet = VecSpet<SSpan>;
+
+    fn spet(spans: &[(u64, u64)]) -> Spet {
+        Spet::from_sorted_iter(
+            spans.iter().map(|(start, end)| SSpan::new(*start, *end)))
+    }
+
+    fn encode(spet: &Spet) -> Vec<u8> {
+        let mut out = Vec::new();
+        spet.write_to(&mut out).unwrap();
+        out
+    }
+
+    mod round_trip {
+        use super::{spet, encode, Spet};
+
+        fn check(original: Spet) {
+            let encoded = encode(&original);
+            assert_eq!(Spet::read_from(&encoded[..]).unwrap(), original);
+        }
+
+        #[test]
+        fn empty() {
+            check(spet(&[]));
+        }
+
+        #[test]
+        fn several() {
+            check(spet(&[(0, 0), (3, 10), (200, 1000), (1 << 40, 1 << 41)]));
+        }
+
+        #[test]
+        fn extremes() {
+            check(spet(&[(0, 1), (u64::MAX - 1, u64::MAX)]));
+            check(spet(&[(0, u64::MAX)]));
+        }
+
+        #[test]
+        fn back_to_back() {
+            let first = spet(&[(1, 2)]);
+            let second = spet(&[(5, 9), (12, 13)]);
+            let mut encoded = encode(&first);
+            encoded.extend(encode(&second));
+
+            let mut reader = &encoded[..];
+            assert_eq!(Spet::read_from(&mut reader).unwrap(), first);
+            assert_eq!(Spet::read_from(&mut reader).unwrap(), second);
+            assert!(reader.is_empty());
+        }
+    }
+
+    #[test]
+    fn encoding() {
+        let encoded = encode(&spet(&[(3, 10), (200, 1000)]));
+
+        let mut expected = MAGIC.to_vec();
+        expected.extend([VERSION, 2, 3, 7, 190, 1, 0xa0, 6]);
+        assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn compact() {
+        // Minute-granularity sessions over a day, as seconds since the epoch.
+        let day = 1_700_000_000;
+        let sessions = spet(&(0..100)
+            .map(|i| (day + i * 600, day + i * 600 + 300))
+            .collect::<Vec<_>>());
+
+        // Each pair of deltas takes 4 bytes, versus 16 for two u64s.
+        assert_eq!(encode(&sessions).len(), 5 + 1 + 5 + 2 + 99 * 4);
+    }
+
+    mod errors {
+        use crate::binary::{DecodeError, MAGIC, VERSION};
+        use super::{spet, encode, Spet};
+
+        fn decode(bytes: &[u8]) -> DecodeError {
+            Spet::read_from(bytes).unwrap_err()
+        }
+
+        fn with_header(body: &[u8]) -> Vec<u8> {
+            let mut bytes = MAGIC.to_vec();
+            bytes.push(VERSION);
+            bytes.extend(body);
+            bytes
+        }
+
+        #[test]
+        fn bad_magic() {
+            assert!(matches!(decode(b"SPOT\x01\x00"), DecodeError::BadMagic));
+        }
+
+        #[test]
+        fn unsupported_version() {
+            assert!(matches!(decode(b"SPET\x02\x00"),
+                             DecodeError::UnsupportedVersion(2)));
+        }
+
+        #[test]
+        fn truncated() {
+            let encoded = encode(&spet(&[(3, 10), (200, 1000)]));
+
+            for len in 0..encoded.len() {
+                assert!(matches!(decode(&encoded[..len]),
+                                 DecodeError::Truncated));
+            }
+        }
+
+        #[test]
+        fn huge_count() {
+            let bytes = with_header(&[0xff, 0xff, 0xff, 0xff, 0x0f, 1, 1]);
+            assert!(matches!(decode(&bytes), DecodeError::Truncated));
+        }
+
+        #[test]
+        fn varint_too_long() {
+            let bytes = with_header(&[0x80; 11]);
+            assert!(matches!(decode(&bytes), DecodeError::VarintTooLong));
+        }
+
+        #[test]
+        fn varint_overflow() {
+            let mut body = vec![0xff; 9];
+            body.push(0x02);
+            assert!(matches!(decode(&with_header(&body)),
+                             DecodeError::Overflow));
+        }
+
+        #[test]
+        fn bound_overflow() {
+            // One span starting at u64::MAX with a length of 1.
+            let mut body = vec![1];
+            body.extend([0xff; 9]);
+            body.extend([0x01, 0x01]);
+            assert!(matches!(decode(&with_header(&body)),
+                             DecodeError::Overflow));
+        }
+    }
+}
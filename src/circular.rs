@@ -0,0 +1,451 @@
+use crate::span::{Span, CreatableSpan};
+use crate::vecspet::VecSpet;
+use crate::measure::Measurable;
+
+
+/**
+ * A set of arcs on a circular domain, ex: the hours of the day (where
+ * `22:00–02:00` wraps around midnight) or the days of the week.
+ *
+ * Positions on the circle are phases: measures from the start of the cycle,
+ * from zero up to (but not including) the period. Ex: with a period of a day,
+ * a phase of `Duration::from_secs(3600)` is 1am. An arc that wraps is kept as
+ * two spans, one ending at the period and one starting at zero, so
+ * spans() is always an ordinary spet within `0–period`. Arcs that touch are
+ * always merged.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircularSpet<S: CreatableSpan>
+        where S::Domain: Measurable<Measure = S::Domain> + Default {
+    period: S::Domain,
+    spans: VecSpet<S>,
+}
+
+
+impl<S: CreatableSpan> CircularSpet<S>
+        where S::Domain: Measurable<Measure = S::Domain> + Default {
+    pub fn new(period: S::Domain) -> Self {
+        assert!(period > S::Domain::default());
+        CircularSpet { period, spans: VecSpet::default() }
+    }
+
+    /**
+     * Builds a circular spet from arcs given as `(start, end)` phases. An arc
+     * whose end is before its start wraps around (ex: `(22, 2)` with a period
+     * of 24), and one whose start and end are equal is empty. The arcs may
+     * be in any order and may overlap.
+     */
+    pub fn from_arcs<I: IntoIterator<Item = (S::Domain, S::Domain)>>(
+            period: S::Domain,
+            arcs: I) -> Self {
+        let zero = S::Domain::default();
+        assert!(period > zero);
+
+        let mut spans: Vec<S> = Vec::new();
+        for (start, end) in arcs {
+            assert!(start >= zero && start < period);
+            assert!(end >= zero && end <= period);
+            if start <= end {
+                spans.push(S::new(start, end));
+            } else {
+                spans.push(S::new(start, period.clone()));
+                spans.push(S::new(zero.clone(), end));
+            }
+        }
+
+        spans.sort();
+        CircularSpet { period, spans: VecSpet::coalesce(spans) }
+    }
+
+    /**
+     * Wraps a linear spet around a circle whose cycles start at `origin` and
+     * last `period`, ex: folding a user's sessions over several months into
+     * the hours of the day they were online.
+     *
+     * `origin` must be no later than any span in `spet`. Spans at least a
+     * period long cover the whole circle.
+     */
+    pub fn fold<L: CreatableSpan>(spet: &VecSpet<L>,
+            origin: &L::Domain,
+            period: S::Domain) -> Self
+            where L::Domain: Measurable<Measure = S::Domain> {
+        assert!(period > S::Domain::default());
+        let mut spans: Vec<S> = Vec::new();
+        for span in spet {
+            spans.extend(fold_span(span, origin, &period));
+        }
+
+        spans.sort();
+        CircularSpet { period, spans: VecSpet::coalesce(spans) }
+    }
+
+    pub fn period(&self) -> &S::Domain {
+        &self.period
+    }
+
+    /**
+     * The spet's arcs as spans within `0–period`, with wrapping arcs split
+     * at zero.
+     */
+    pub fn spans(&self) -> &VecSpet<S> {
+        &self.spans
+    }
+
+    /**
+     * The spet's arcs as `(start, end)` phases, with an arc that wraps around
+     * zero given as one arc whose end is before its start. A spet that covers
+     * the whole circle is given as `(0, period)`.
+     */
+    pub fn arcs(&self) -> Vec<(S::Domain, S::Domain)> {
+        let mut arcs: Vec<(S::Domain, S::Domain)> = (&self.spans).into_iter()
+            .map(|span| (span.start().clone(), span.end().clone()))
+            .collect();
+
+        let zero = S::Domain::default();
+        let wraps = arcs.len() > 1
+            && arcs[0].0 == zero
+            && arcs[arcs.len() - 1].1 == self.period;
+        if wraps {
+            let (_, end) = arcs.remove(0);
+            arcs.last_mut().unwrap().1 = end;
+        }
+        arcs
+    }
+
+    pub fn contains(&self, phase: &S::Domain) -> bool {
+        self.spans.contains(phase)
+    }
+
+    /**
+     * The total measure of the arcs. This is at most the period.
+     */
+    pub fn measure(&self) -> S::Domain {
+        self.spans.measure()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    pub fn union(&self, other: &CircularSpet<S>) -> CircularSpet<S> {
+        assert!(self.period == other.period);
+        CircularSpet {
+            period: self.period.clone(),
+            spans: merge_touching(self.spans.union(&other.spans)),
+        }
+    }
+
+    pub fn intersection(&self, other: &CircularSpet<S>) -> CircularSpet<S> {
+        assert!(self.period == other.period);
+        CircularSpet {
+            period: self.period.clone(),
+            spans: merge_touching(
+                self.spans.intersection(&other.spans)),
+        }
+    }
+
+    /**
+     * The arcs covered by at least `n` of `spets`, like
+     * overlapping::n_overlapping(). Every spet must have the given period
+     * (which is what the result has even if there are no spets).
+     */
+    pub fn n_overlapping(n: usize,
+            period: S::Domain,
+            spets: &[&CircularSpet<S>]) -> CircularSpet<S> {
+        assert!(period > S::Domain::default());
+        assert!(spets.iter().all(|spet| spet.period == period));
+        CircularSpet {
+            period,
+            spans: merge_touching(crate::overlapping::n_overlapping(
+                n, spets.iter().map(|spet| &spet.spans))),
+        }
+    }
+
+    /**
+     * How many of `spets` cover each part of the circle.
+     *
+     * Gives spans covering all of `0–period` in order, each with its depth.
+     * Neighboring spans always have different depths. Every spet must have
+     * the given period.
+     */
+    pub fn depth_profile(period: S::Domain, spets: &[&CircularSpet<S>])
+            -> Vec<(S, usize)> {
+        assert!(period > S::Domain::default());
+        assert!(spets.iter().all(|spet| spet.period == period));
+        depth_profile(&period, spets.iter().flat_map(|spet| &spet.spans))
+    }
+
+    /**
+     * Like fold(), but rather than merging the folded spans, counts how many
+     * of them cover each part of the circle. Ex: folding a month of sessions
+     * into a day gives how many days someone was online at each time of day.
+     *
+     * Gives spans covering all of `0–period` in order, each with its depth.
+     */
+    pub fn fold_depth_profile<L: CreatableSpan>(spet: &VecSpet<L>,
+            origin: &L::Domain,
+            period: S::Domain) -> Vec<(S, usize)>
+            where L::Domain: Measurable<Measure = S::Domain> {
+        assert!(period > S::Domain::default());
+        let folded = spet.into_iter()
+            .flat_map(|span| fold_span::<S, L>(span, origin, &period));
+        depth_profile(&period, folded)
+    }
+}
+
+
+fn merge_touching<S: CreatableSpan>(spet: VecSpet<S>) -> VecSpet<S> {
+    VecSpet::coalesce(spet.spans)
+}
+
+
+// Folds one span onto the circle, giving one piece (or two if it wraps).
+fn fold_span<S: CreatableSpan, L: Span>(span: &L,
+        origin: &L::Domain,
+        period: &S::Domain) -> Vec<S>
+        where S::Domain: Measurable<Measure = S::Domain> + Default,
+              L::Domain: Measurable<Measure = S::Domain> {
+    assert!(span.start() >= origin);
+    let zero = S::Domain::default();
+    let length = span.start().measure_to(span.end());
+    if length >= *period {
+        return vec![S::new(zero, period.clone())];
+    } else if length == zero {
+        return vec![];
+    }
+
+    let cycle_start = span.start().floor_to_grid(origin, period);
+    let start = cycle_start.measure_to(span.start());
+    let end = start.offset_forward(&length);
+    if end <= *period {
+        vec![S::new(start, end)]
+    } else {
+        vec![
+            S::new(start, period.clone()),
+            S::new(zero, end.offset_backward(period)),
+        ]
+    }
+}
+
+
+// Sweeps over spans within `0–period` (which may overlap) to find how many
+// cover each part of it.
+fn depth_profile<S, I>(period: &S::Domain, spans: I) -> Vec<(S, usize)>
+        where S: CreatableSpan,
+              S::Domain: Measurable<Measure = S::Domain> + Default,
+              I: IntoIterator,
+              I::Item: Span<Domain = S::Domain> {
+    let mut changes: Vec<(S::Domain, isize)> = Vec::new();
+    for span in spans {
+        if span.start() < span.end() {
+            changes.push((span.start().clone(), 1));
+            changes.push((span.end().clone(), -1));
+        }
+    }
+    changes.sort();
+
+    let mut profile: Vec<(S, usize)> = Vec::new();
+    let mut position = S::Domain::default();
+    let mut depth: isize = 0;
+    let mut changes = changes.into_iter().peekable();
+    while position < *period {
+        while let Some((_, change)) =
+                changes.next_if(|(at, _)| *at == position) {
+            depth += change;
+        }
+        let next = match changes.peek() {
+            Some((at, _)) => at.clone(),
+            None => period.clone(),
+        };
+
+        match profile.last_mut() {
+            Some((last, last_depth)) if *last_depth == depth as usize => {
+                *last = S::new(last.start().clone(), next.clone());
+            },
+            _ => profile.push(
+                (S::new(position, next.clone()), depth as usize)),
+        }
+        position = next;
+    }
+    profile
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::circular::CircularSpet;
+    use crate::span::{SimpleSpan, CreatableSpan};
+    use crate::vecspet::VecSpet;
+
+    // Hours of the day.
+    type SSpan = SimpleSpan<i64>;
+    type Spet = VecSpet<SSpan>;
+    type Circle = CircularSpet<SSpan>;
+
+    fn circle(arcs: &[(i64, i64)]) -> Circle {
+        Circle::from_arcs(24, arcs.iter().cloned())
+    }
+
+    fn profile(entries: &[(i64, i64, usize)]) -> Vec<(SSpan, usize)> {
+        entries.iter()
+            .map(|(start, end, depth)| (SSpan::new(*start, *end), *depth))
+            .collect()
+    }
+
+    mod from_arcs {
+        use super::*;
+
+        #[test]
+        fn wrapping() {
+            let night = circle(&[(22, 2)]);
+
+            assert_eq!(night.spans(), &Spet::from_sorted_iter(vec![
+                SSpan::new(0, 2),
+                SSpan::new(22, 24),
+            ]));
+            assert_eq!(night.arcs(), vec![(22, 2)]);
+            assert_eq!(night.measure(), 4);
+        }
+
+        #[test]
+        fn overlapping_and_empty() {
+            let a = circle(&[(20, 23), (5, 5), (1, 3), (22, 2)]);
+
+            assert_eq!(a.arcs(), vec![(20, 3)]);
+        }
+
+        #[test]
+        fn whole_circle() {
+            let a = circle(&[(0, 24)]);
+
+            assert_eq!(a.arcs(), vec![(0, 24)]);
+            assert!(a.contains(&0) && a.contains(&23));
+        }
+    }
+
+    #[test]
+    fn contains() {
+        let night = circle(&[(22, 2)]);
+
+        let hours: Vec<i64> = (0..24).filter(|h| night.contains(h)).collect();
+        assert_eq!(hours, vec![0, 1, 22, 23]);
+    }
+
+    mod fold {
+        use super::*;
+
+        #[test]
+        fn wraps_spans() {
+            // Hours since midnight on the first day.
+            let sessions = Spet::from_sorted_iter(vec![
+                SSpan::new(9, 10),
+                SSpan::new(46, 50),
+                SSpan::new(80, 81),
+            ]);
+
+            let folded = Circle::fold(&sessions, &0, 24);
+            assert_eq!(folded.arcs(), vec![(8, 10), (22, 2)]);
+        }
+
+        #[test]
+        fn origin() {
+            let sessions = Spet::from_sorted_iter(vec![SSpan::new(9, 10)]);
+
+            assert_eq!(Circle::fold(&sessions, &-3, 24).arcs(),
+                       vec![(12, 13)]);
+        }
+
+        #[test]
+        fn long_spans_cover_everything() {
+            let sessions = Spet::from_sorted_iter(vec![SSpan::new(5, 40)]);
+
+            assert_eq!(Circle::fold(&sessions, &0, 24).arcs(), vec![(0, 24)]);
+        }
+    }
+
+    #[test]
+    fn union_and_intersection() {
+        let night = circle(&[(22, 6)]);
+        let evening = circle(&[(18, 23)]);
+
+        assert_eq!(night.union(&evening).arcs(), vec![(18, 6)]);
+        assert_eq!(night.intersection(&evening).arcs(), vec![(22, 23)]);
+        assert_eq!(night.intersection(&circle(&[(3, 4)])).arcs(),
+                   vec![(3, 4)]);
+    }
+
+    #[test]
+    fn n_overlapping() {
+        let a = circle(&[(22, 4)]);
+        let b = circle(&[(23, 2)]);
+        let c = circle(&[(1, 23)]);
+
+        assert_eq!(Circle::n_overlapping(2, 24, &[&a, &b, &c]).arcs(),
+                   vec![(22, 4)]);
+        assert_eq!(Circle::n_overlapping(3, 24, &[&a, &b, &c]).arcs(),
+                   vec![(1, 2)]);
+    }
+
+    #[test]
+    fn no_spets() {
+        assert!(Circle::n_overlapping(1, 24, &[]).is_empty());
+        assert_eq!(Circle::depth_profile(24, &[]), profile(&[(0, 24, 0)]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn fold_with_zero_period() {
+        Circle::fold(&Spet::from_sorted_iter(vec![SSpan::new(1, 2)]), &0, 0);
+    }
+
+    #[test]
+    fn depth_profile() {
+        let a = circle(&[(22, 4)]);
+        let b = circle(&[(2, 6)]);
+
+        assert_eq!(Circle::depth_profile(24, &[&a, &b]), profile(&[
+            (0, 2, 1),
+            (2, 4, 2),
+            (4, 6, 1),
+            (6, 22, 0),
+            (22, 24, 1),
+        ]));
+    }
+
+    #[test]
+    fn fold_depth_profile() {
+        // Online late every night for three nights, and once at noon.
+        let sessions = Spet::from_sorted_iter(vec![
+            SSpan::new(22, 25),
+            SSpan::new(36, 37),
+            SSpan::new(47, 49),
+            SSpan::new(70, 73),
+        ]);
+
+        assert_eq!(Circle::fold_depth_profile(&sessions, &0, 24), profile(&[
+            (0, 1, 3),
+            (1, 12, 0),
+            (12, 13, 1),
+            (13, 22, 0),
+            (22, 23, 2),
+            (23, 24, 3),
+        ]));
+    }
+
+    #[test]
+    fn time_of_day() {
+        use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+        let hour = Duration::from_secs(3600);
+        let day = hour * 24;
+        let at = |hours: u32| UNIX_EPOCH + hour * hours;
+        let sessions = VecSpet::<SimpleSpan<SystemTime>>::from_sorted_iter(vec![
+            SimpleSpan::new(at(23), at(25)),
+            SimpleSpan::new(at(24 + 23), at(48 + 1)),
+        ]);
+
+        let folded = CircularSpet::<SimpleSpan<Duration>>::fold(
+            &sessions, &SystemTime::UNIX_EPOCH, day);
+        assert_eq!(folded.arcs(), vec![(hour * 23, hour)]);
+    }
+}
@@ -0,0 +1,563 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::hash::Hash;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+use crate::span::CreatableSpan;
+use crate::vecspet::VecSpet;
+
+
+/**
+ * Reads and writes spans as CSV, with one span per row.
+ *
+ * The first row must be a header naming the columns, and the columns can be
+ * in any order (extra columns are ignored). Ex, with the default column
+ * names:
+ *
+ * ```text
+ * key,start,end
+ * alice,1,5
+ * bob,3,8
+ * ```
+ *
+ * Rows don't need to be sorted and their spans can overlap. Quoted fields
+ * (ex: `"smith, jane"`) are supported as long as they don't contain line
+ * breaks, and writing a field with a line break is an error (since it
+ * couldn't be read back).
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvFormat {
+    pub key_column: String,
+    pub start_column: String,
+    pub end_column: String,
+    pub delimiter: char,
+}
+
+
+impl Default for CsvFormat {
+    fn default() -> Self {
+        CsvFormat {
+            key_column: "key".to_string(),
+            start_column: "start".to_string(),
+            end_column: "end".to_string(),
+            delimiter: ',',
+        }
+    }
+}
+
+
+/**
+ * Why a CSV file couldn't be read.
+ */
+#[derive(Debug)]
+pub enum CsvErrorKind {
+    Io(io::Error),
+
+    // The file had no header row.
+    MissingHeader,
+
+    // The header row had no column by the given name.
+    MissingColumn(String),
+
+    // The row had fewer fields than the column being looked for.
+    MissingField(String),
+
+    // The value (given) in the column (given) couldn't be parsed.
+    InvalidValue { column: String, value: String },
+
+    // The row's span ends before it starts.
+    Reversed,
+
+    // A quoted field was never closed.
+    UnterminatedQuote,
+}
+
+
+/**
+ * Error from reading CSV, along with the (1-based) line it happened on.
+ */
+#[derive(Debug)]
+pub struct CsvError {
+    pub line: usize,
+    pub kind: CsvErrorKind,
+}
+
+
+impl Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            CsvErrorKind::Io(err) => err.fmt(f),
+            CsvErrorKind::MissingHeader => write!(f, "missing header row"),
+            CsvErrorKind::MissingColumn(column) =>
+                write!(f, "no column named {:?}", column),
+            CsvErrorKind::MissingField(column) =>
+                write!(f, "row has no {:?} field", column),
+            CsvErrorKind::InvalidValue { column, value } =>
+                write!(f, "invalid {} {:?}", column, value),
+            CsvErrorKind::Reversed => write!(f, "span ends before it starts"),
+            CsvErrorKind::UnterminatedQuote =>
+                write!(f, "quoted field is never closed"),
+        }
+    }
+}
+
+
+impl std::error::Error for CsvError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            CsvErrorKind::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+
+impl CsvFormat {
+    /**
+     * Reads every row into a single spet. The key column isn't needed.
+     */
+    pub fn read_spet<S: CreatableSpan, R: BufRead>(&self, reader: R)
+            -> Result<VecSpet<S>, CsvError>
+            where S::Domain: FromStr {
+        let mut spans: Vec<S> = Vec::new();
+        self.read_rows(reader, false, |_, span| {
+            spans.push(span);
+            Ok(())
+        })?;
+
+        spans.sort();
+        Ok(VecSpet::from_sorted_iter(spans))
+    }
+
+    /**
+     * Reads the rows into a spet per key.
+     */
+    pub fn read_keyed<K, S, R>(&self, reader: R)
+            -> Result<HashMap<K, VecSpet<S>>, CsvError>
+            where K: FromStr + Eq + Hash,
+                  S: CreatableSpan,
+                  S::Domain: FromStr,
+                  R: BufRead {
+        let mut spans: HashMap<K, Vec<S>> = HashMap::new();
+        self.read_rows(reader, true, |(line, key), span| {
+            let key = key.parse::<K>().map_err(|_| CsvError {
+                line,
+                kind: CsvErrorKind::InvalidValue {
+                    column: self.key_column.clone(),
+                    value: key.to_string(),
+                },
+            })?;
+            spans.entry(key).or_default().push(span);
+            Ok(())
+        })?;
+
+        Ok(spans.into_iter().map(|(key, mut spans)| {
+            spans.sort();
+            (key, VecSpet::from_sorted_iter(spans))
+        }).collect())
+    }
+
+    /**
+     * Writes a spet with a `start` and `end` column (using this format's
+     * column names). Fails with io::ErrorKind::InvalidInput if a column name
+     * or value contains a line break.
+     */
+    pub fn write_spet<S: CreatableSpan, W: Write>(&self,
+            mut writer: W,
+            spet: &VecSpet<S>) -> io::Result<()>
+            where S::Domain: Display {
+        self.write_row(&mut writer, &[&self.start_column, &self.end_column])?;
+        for span in spet {
+            self.write_row(&mut writer, &[
+                &span.start().to_string(),
+                &span.end().to_string(),
+            ])?;
+        }
+        Ok(())
+    }
+
+    /**
+     * Writes spets with a key, start, and end column. Keys are written in
+     * ascending order.
+     *
+     * Fails with io::ErrorKind::InvalidInput if a key contains a line break,
+     * before anything is written.
+     */
+    pub fn write_keyed<K, S, W>(&self,
+            mut writer: W,
+            spets: &HashMap<K, VecSpet<S>>) -> io::Result<()>
+            where K: Display + Ord,
+                  S: CreatableSpan,
+                  S::Domain: Display,
+                  W: Write {
+        let mut entries: Vec<(&K, &VecSpet<S>)> = spets.iter().collect();
+        entries.sort_by_key(|(key, _)| *key);
+        let entries = entries.into_iter()
+            .map(|(key, spet)| {
+                let key_text = key.to_string();
+                check_field(&key_text)?;
+                Ok((key_text, spet))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        self.write_row(&mut writer, &[
+            &self.key_column, &self.start_column, &self.end_column])?;
+        for (key_text, spet) in entries {
+            for span in spet {
+                self.write_row(&mut writer, &[
+                    &key_text,
+                    &span.start().to_string(),
+                    &span.end().to_string(),
+                ])?;
+            }
+        }
+        Ok(())
+    }
+
+    // Calls `visit` with the line number, key field, and span of each row.
+    // The key field is empty unless `keyed` is true.
+    fn read_rows<S: CreatableSpan, R: BufRead>(&self,
+            reader: R,
+            keyed: bool,
+            mut visit: impl FnMut((usize, &str), S) -> Result<(), CsvError>)
+            -> Result<(), CsvError>
+            where S::Domain: FromStr {
+        let mut lines = reader.lines().enumerate()
+            .map(|(i, line)| (i + 1, line))
+            .filter(|(_, line)| match line {
+                Ok(line) => !line.trim().is_empty(),
+                Err(_) => true,
+            });
+
+        let (line, header) = match lines.next() {
+            Some((line, header)) => (line, header.map_err(
+                |err| CsvError { line, kind: CsvErrorKind::Io(err) })?),
+            None => return Err(CsvError {
+                line: 1,
+                kind: CsvErrorKind::MissingHeader,
+            }),
+        };
+        let header = self.split(&header)
+            .map_err(|kind| CsvError { line, kind })?;
+        let find = |column: &str| header.iter()
+            .position(|name| name == column)
+            .ok_or_else(|| CsvError {
+                line,
+                kind: CsvErrorKind::MissingColumn(column.to_string()),
+            });
+        let key_index = match keyed {
+            true => Some(find(&self.key_column)?),
+            false => None,
+        };
+        let start_index = find(&self.start_column)?;
+        let end_index = find(&self.end_column)?;
+
+        for (line, text) in lines {
+            let error = |kind| CsvError { line, kind };
+            let text = text.map_err(|err| error(CsvErrorKind::Io(err)))?;
+            let fields = self.split(&text).map_err(error)?;
+
+            let field = |index: usize, column: &str| fields.get(index)
+                .map(|field| field.as_str())
+                .ok_or_else(|| error(
+                    CsvErrorKind::MissingField(column.to_string())));
+            let parse = |index: usize, column: &str| {
+                let value = field(index, column)?;
+                value.trim().parse::<S::Domain>().map_err(|_| error(
+                    CsvErrorKind::InvalidValue {
+                        column: column.to_string(),
+                        value: value.to_string(),
+                    }))
+            };
+
+            let start = parse(start_index, &self.start_column)?;
+            let end = parse(end_index, &self.end_column)?;
+            if start > end {
+                return Err(error(CsvErrorKind::Reversed));
+            }
+            let key = match key_index {
+                Some(index) => field(index, &self.key_column)?,
+                None => "",
+            };
+
+            visit((line, key), S::new(start, end))?;
+        }
+
+        Ok(())
+    }
+
+    fn split(&self, line: &str) -> Result<Vec<String>, CsvErrorKind> {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let mut fields = Vec::new();
+        let mut chars = line.chars().peekable();
+        loop {
+            let mut field = String::new();
+            if chars.peek() == Some(&'"') {
+                chars.next();
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        },
+                        Some('"') => break,
+                        Some(c) => field.push(c),
+                        None => return Err(CsvErrorKind::UnterminatedQuote),
+                    }
+                }
+                // Anything between the closing quote and the delimiter is
+                // kept, as most CSV readers do.
+                while let Some(c) = chars.next_if(|c| *c != self.delimiter) {
+                    field.push(c);
+                }
+            } else {
+                while let Some(c) = chars.next_if(|c| *c != self.delimiter) {
+                    field.push(c);
+                }
+            }
+            fields.push(field);
+
+            if chars.next().is_none() {
+                return Ok(fields);
+            }
+        }
+    }
+
+    fn write_row<W: Write>(&self, writer: &mut W, fields: &[&String])
+            -> io::Result<()> {
+        for field in fields {
+            check_field(field)?;
+        }
+
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                write!(writer, "{}", self.delimiter)?;
+            }
+
+            let needs_quotes = field.contains(self.delimiter)
+                || field.contains('"');
+            if needs_quotes {
+                write!(writer, "\"{}\"", field.replace('"', "\"\""))?;
+            } else {
+                write!(writer, "{}", field)?;
+            }
+        }
+        writeln!(writer)
+    }
+}
+
+
+// Rows are read a line at a time, so a field with a line break in it can't be
+// written.
+fn check_field(field: &str) -> io::Result<()> {
+    if field.contains(['\n', '\r']) {
+        Err(io::Error::new(io::ErrorKind::InvalidInput,
+                           "CSV fields can't contain line breaks"))
+    } else {
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::csv::{CsvFormat, CsvErrorKind};
+    use crate::span::{SimpleSpan, CreatableSpan};
+    use crate::vecspet::VecSpet;
+    use crate::overlapping::n_overlapping;
+
+    type SSpan = SimpleSpan<i64>;
+    type Spet = VecSpet<SSpan>;
+
+    fn spet(spans: &[(i64, i64)]) -> Spet {
+        Spet::from_sorted_iter(
+            spans.iter().map(|(start, end)| SSpan::new(*start, *end)))
+    }
+
+    #[test]
+    fn read_spet() {
+        let text = "start,end\n8,9\n1,5\n3,7\n";
+
+        let result: Spet = CsvFormat::default()
+            .read_spet(text.as_bytes()).unwrap();
+        assert_eq!(result, spet(&[(1, 7), (8, 9)]));
+    }
+
+    #[test]
+    fn read_keyed() {
+        let text = "user,start,end\nalice,1,5\nbob,3,8\nalice,4,6\n";
+        let format = CsvFormat {
+            key_column: "user".to_string(),
+            ..CsvFormat::default()
+        };
+
+        let result: HashMap<String, Spet> =
+            format.read_keyed(text.as_bytes()).unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result["alice"], spet(&[(1, 6)]));
+        assert_eq!(result["bob"], spet(&[(3, 8)]));
+    }
+
+    #[test]
+    fn numeric_keys() {
+        let text = "key,start,end\n7,1,5\n";
+
+        let result: HashMap<u32, Spet> = CsvFormat::default()
+            .read_keyed(text.as_bytes()).unwrap();
+        assert_eq!(result[&7], spet(&[(1, 5)]));
+    }
+
+    #[test]
+    fn custom_columns_and_order() {
+        let text = "to;note;from\r\n\
+                    5;\"a; b\";1\r\n\
+                    \r\n\
+                    9;\"say \"\"hi\"\"\";8\r\n";
+        let format = CsvFormat {
+            start_column: "from".to_string(),
+            end_column: "to".to_string(),
+            delimiter: ';',
+            ..CsvFormat::default()
+        };
+
+        let result: Spet = format.read_spet(text.as_bytes()).unwrap();
+        assert_eq!(result, spet(&[(1, 5), (8, 9)]));
+    }
+
+    #[test]
+    fn write_spet() {
+        let logged_in = vec![
+            spet(&[(1, 5), (7, 9)]),
+            spet(&[(3, 8)]),
+        ];
+        let overlap: Spet = n_overlapping(2, &logged_in);
+
+        let mut out = Vec::new();
+        CsvFormat::default().write_spet(&mut out, &overlap).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "start,end\n3,5\n7,8\n");
+    }
+
+    #[test]
+    fn keyed_round_trip() {
+        let mut spets: HashMap<String, Spet> = HashMap::new();
+        spets.insert("smith, jane".to_string(), spet(&[(1, 2), (4, 5)]));
+        spets.insert("doe".to_string(), spet(&[(3, 8)]));
+
+        let mut out = Vec::new();
+        CsvFormat::default().write_keyed(&mut out, &spets).unwrap();
+        assert_eq!(String::from_utf8(out.clone()).unwrap(),
+                   "key,start,end\ndoe,3,8\n\"smith, jane\",1,2\n\
+                    \"smith, jane\",4,5\n");
+
+        let result: HashMap<String, Spet> = CsvFormat::default()
+            .read_keyed(&out[..]).unwrap();
+        assert_eq!(result, spets);
+    }
+
+    #[test]
+    fn keys_with_line_breaks() {
+        for key in ["line\nbreak", "carriage\rreturn"].iter() {
+            let mut spets: HashMap<String, Spet> = HashMap::new();
+            spets.insert("doe".to_string(), spet(&[(3, 8)]));
+            spets.insert(key.to_string(), spet(&[(1, 2)]));
+
+            let mut out = Vec::new();
+            let err = CsvFormat::default().write_keyed(&mut out, &spets)
+                .unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+            assert!(out.is_empty());
+        }
+    }
+
+    mod errors {
+        use crate::csv::{CsvFormat, CsvError, CsvErrorKind};
+
+        fn read(text: &str) -> CsvError {
+            CsvFormat::default().read_spet::<super::SSpan, _>(text.as_bytes())
+                .unwrap_err()
+        }
+
+        #[test]
+        fn missing_header() {
+            assert!(matches!(read("").kind, CsvErrorKind::MissingHeader));
+        }
+
+        #[test]
+        fn missing_column() {
+            let err = read("start,stop\n1,2\n");
+
+            assert_eq!(err.line, 1);
+            assert!(matches!(err.kind,
+                             CsvErrorKind::MissingColumn(c) if c == "end"));
+        }
+
+        #[test]
+        fn invalid_value_reports_line() {
+            let err = read("start,end\n1,2\n\n3,x\n");
+
+            assert_eq!(err.line, 4);
+            assert!(matches!(
+                err.kind,
+                CsvErrorKind::InvalidValue { column, value }
+                    if column == "end" && value == "x"));
+        }
+
+        #[test]
+        fn missing_field() {
+            let err = read("start,end\n1\n");
+
+            assert_eq!(err.line, 2);
+            assert!(matches!(err.kind, CsvErrorKind::MissingField(_)));
+        }
+
+        #[test]
+        fn reversed() {
+            let err = read("start,end\n5,2\n");
+
+            assert_eq!(err.line, 2);
+            assert!(matches!(err.kind, CsvErrorKind::Reversed));
+        }
+
+        #[test]
+        fn unterminated_quote() {
+            let err = read("start,end\n\"1,2\n");
+
+            assert_eq!(err.line, 2);
+            assert!(matches!(err.kind, CsvErrorKind::UnterminatedQuote));
+        }
+
+        #[test]
+        fn invalid_key() {
+            let err = CsvFormat::default()
+                .read_keyed::<u32, super::SSpan, _>(
+                    "key,start,end\n1,1,2\nbob,1,2\n".as_bytes())
+                .unwrap_err();
+
+            assert_eq!(err.line, 3);
+            assert!(matches!(err.kind, CsvErrorKind::InvalidValue { .. }));
+        }
+
+        #[test]
+        fn first_error_wins() {
+            let err = CsvFormat::default()
+                .read_keyed::<u32, super::SSpan, _>(
+                    "key,start,end\nnotanumber,1,2\n5,1,2\n6,x,3\n"
+                        .as_bytes())
+                .unwrap_err();
+
+            assert_eq!(err.to_string(), "line 2: invalid key \"notanumber\"");
+        }
+    }
+
+    #[test]
+    fn error_display() {
+        let err = CsvFormat::default()
+            .read_spet::<SSpan, _>("start,end\n1,x\n".as_bytes())
+            .unwrap_err();
+
+        assert!(matches!(err.kind, CsvErrorKind::InvalidValue { .. }));
+        assert_eq!(err.to_string(), "line 2: invalid end \"x\"");
+    }
+}
@@ -0,0 +1,365 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+use std::ops::{Add, Sub};
+use std::str::FromStr;
+use crate::measure::Measurable;
+use crate::span::{SimpleSpan, CreatableSpan};
+
+
+/**
+ * An f64 that's never NaN, and so can be totally ordered.
+ *
+ * Spans need a domain that implements Ord, which f64 doesn't (since NaN isn't
+ * less than, equal to, or greater than anything). Ex:
+ * `SimpleSpan<OrderedF64>` works wherever `SimpleSpan<f64>` would be wanted.
+ *
+ * Negative zero is stored as positive zero, so that values which compare
+ * equal as f64s compare equal here too.
+ */
+#[derive(Debug, Copy, Clone, Default)]
+pub struct OrderedF64(f64);
+
+
+impl OrderedF64 {
+    /**
+     * Wraps `value`, or returns None if it's NaN.
+     */
+    pub fn new(value: f64) -> Option<OrderedF64> {
+        if value.is_nan() {
+            None
+        } else if value == 0.0 {
+            Some(OrderedF64(0.0))
+        } else {
+            Some(OrderedF64(value))
+        }
+    }
+
+    pub fn get(self) -> f64 {
+        self.0
+    }
+}
+
+
+/**
+ * Panics if `value` is NaN. Use OrderedF64::new() to handle NaN instead.
+ */
+impl From<f64> for OrderedF64 {
+    fn from(value: f64) -> Self {
+        OrderedF64::new(value).expect("OrderedF64 can't be NaN")
+    }
+}
+
+
+// Every one of these converts to an f64 exactly, which lets spans over them be
+// passed to VecSpet::from_sorted_iter to build a spet over OrderedF64s.
+macro_rules! impl_from_lossless {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for OrderedF64 {
+                fn from(value: $t) -> Self {
+                    OrderedF64::from(f64::from(value))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_lossless!(f32, i8, i16, i32, u8, u16, u32);
+
+
+impl From<OrderedF64> for f64 {
+    fn from(value: OrderedF64) -> Self {
+        value.0
+    }
+}
+
+
+impl PartialEq for OrderedF64 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+
+impl Eq for OrderedF64 {}
+
+
+impl PartialOrd for OrderedF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+
+impl Ord for OrderedF64 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+
+impl std::hash::Hash for OrderedF64 {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+
+impl Add for OrderedF64 {
+    type Output = OrderedF64;
+
+    /**
+     * Panics if the result is NaN (ex: infinity plus negative infinity).
+     * Measurable::offset_forward() never does.
+     */
+    fn add(self, other: OrderedF64) -> OrderedF64 {
+        OrderedF64::from(self.0 + other.0)
+    }
+}
+
+
+impl Sub for OrderedF64 {
+    type Output = OrderedF64;
+
+    /**
+     * Panics if the result is NaN (ex: infinity minus infinity).
+     * Measurable::measure_to() and offset_backward() never do.
+     */
+    fn sub(self, other: OrderedF64) -> OrderedF64 {
+        OrderedF64::from(self.0 - other.0)
+    }
+}
+
+
+impl Display for OrderedF64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+
+/**
+ * Error from parsing an OrderedF64.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseOrderedF64Error {
+    Invalid(std::num::ParseFloatError),
+    NaN,
+}
+
+
+impl Display for ParseOrderedF64Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseOrderedF64Error::Invalid(err) => err.fmt(f),
+            ParseOrderedF64Error::NaN => write!(f, "NaN isn't allowed"),
+        }
+    }
+}
+
+
+impl std::error::Error for ParseOrderedF64Error {}
+
+
+impl FromStr for OrderedF64 {
+    type Err = ParseOrderedF64Error;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let value = text.parse::<f64>()
+            .map_err(ParseOrderedF64Error::Invalid)?;
+        OrderedF64::new(value).ok_or(ParseOrderedF64Error::NaN)
+    }
+}
+
+
+/**
+ * Unlike `+` and `-`, measures and offsets never give NaN: the measure
+ * between two equal infinities is zero, and infinities stay where they are
+ * when offset (ex: shifting a span that ends at infinity). floor_to_grid()
+ * panics if `origin` or `width` is infinite.
+ */
+impl Measurable for OrderedF64 {
+    type Measure = OrderedF64;
+
+    fn measure_to(&self, later: &Self) -> Self::Measure {
+        if later == self {
+            OrderedF64(0.0)
+        } else {
+            *later - *self
+        }
+    }
+
+    fn offset_forward(&self, by: &Self::Measure) -> Self {
+        if self.0.is_infinite() {
+            *self
+        } else {
+            *self + *by
+        }
+    }
+
+    fn offset_backward(&self, by: &Self::Measure) -> Self {
+        if self.0.is_infinite() {
+            *self
+        } else {
+            *self - *by
+        }
+    }
+
+    // Rounding isn't saturation, and infinities stay put, so these never
+    // fail.
+    fn checked_offset_forward(&self, by: &Self::Measure) -> Option<Self> {
+        Some(self.offset_forward(by))
+    }
+
+    fn checked_offset_backward(&self, by: &Self::Measure) -> Option<Self> {
+        Some(self.offset_backward(by))
+    }
+
+    fn measure_as_f64(measure: &Self::Measure) -> f64 {
+        measure.0
+    }
+
+    fn floor_to_grid(&self, origin: &Self, width: &Self::Measure) -> Self {
+        let steps = ((self.0 - origin.0) / width.0).floor();
+        OrderedF64::from(origin.0 + steps * width.0)
+    }
+
+    fn nth_grid_point(&self, width: &Self::Measure, n: u64) -> Option<Self> {
+        if self.0.is_infinite() || n == 0 {
+            return Some(*self);
+        }
+
+        let point = self.0 + n as f64 * width.0;
+        if point.is_nan() {
+            None
+        } else {
+            Some(OrderedF64(point))
+        }
+    }
+}
+
+
+impl SimpleSpan<OrderedF64> {
+    /**
+     * Creates a span from raw floats. Panics if either is NaN.
+     */
+    pub fn from_f64(start: f64, end: f64) -> Self {
+        SimpleSpan::new(start.into(), end.into())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::float::{OrderedF64, ParseOrderedF64Error};
+    use crate::measure::Measurable;
+    use crate::span::{Span, SimpleSpan, CreatableSpan};
+    use crate::vecspet::VecSpet;
+
+    type FSpan = SimpleSpan<OrderedF64>;
+    type Spet = VecSpet<FSpan>;
+
+    #[test]
+    fn rejects_nan() {
+        assert_eq!(OrderedF64::new(f64::NAN), None);
+        assert_eq!("NaN".parse::<OrderedF64>(),
+                   Err(ParseOrderedF64Error::NaN));
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_nan_panics() {
+        let _ = OrderedF64::from(f64::NAN);
+    }
+
+    #[test]
+    fn ordering() {
+        let mut values: Vec<OrderedF64> = vec![
+            2.5.into(),
+            f64::NEG_INFINITY.into(),
+            (-1.0).into(),
+            f64::INFINITY.into(),
+            0.0.into(),
+        ];
+        values.sort();
+
+        assert_eq!(values.into_iter().map(f64::from).collect::<Vec<_>>(),
+                   vec![f64::NEG_INFINITY, -1.0, 0.0, 2.5, f64::INFINITY]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn adding_opposite_infinities_panics() {
+        let _ = OrderedF64::from(f64::INFINITY)
+            + OrderedF64::from(f64::NEG_INFINITY);
+    }
+
+    #[test]
+    #[should_panic]
+    fn subtracting_infinities_panics() {
+        let infinity = OrderedF64::from(f64::INFINITY);
+        let _ = infinity - infinity;
+    }
+
+    #[test]
+    fn negative_zero() {
+        assert_eq!(OrderedF64::from(-0.0), OrderedF64::from(0.0));
+    }
+
+    #[test]
+    fn readme_example() {
+        let a = Spet::from_sorted_iter(vec![
+            FSpan::from_f64(1.2, 4.0),
+            FSpan::from_f64(8.1, 8.4),
+        ]);
+        let b = Spet::from_sorted_iter(vec![FSpan::from_f64(3.0, 8.2)]);
+
+        assert_eq!(a.union(&b).to_string(), "{1.2–8.4}");
+        assert_eq!(a.intersection(&b).to_string(), "{3–4, 8.1–8.2}");
+    }
+
+    #[test]
+    fn parses_notation() {
+        let spet: Spet = "{1.2–4, 8.1–8.4}".parse().unwrap();
+
+        assert_eq!(spet, Spet::from_sorted_iter(vec![
+            FSpan::from_f64(1.2, 4.0),
+            FSpan::from_f64(8.1, 8.4),
+        ]));
+    }
+
+    #[test]
+    fn from_integer_spans() {
+        let spans = vec![SimpleSpan::new(1i32, 3), SimpleSpan::new(5, 6)];
+        let spet = Spet::from_sorted_iter(&spans);
+
+        assert_eq!(spet.to_string(), "{1–3, 5–6}");
+    }
+
+    #[test]
+    fn measurable() {
+        let span = FSpan::from_f64(1.5, 4.0);
+        assert_eq!(span.measure(), 2.5.into());
+
+        let spet = Spet::from_sorted_iter(vec![span]);
+        assert_eq!(spet.dilate(0.5.into(), 1.0.into()).to_string(), "{1–5}");
+
+        let value = OrderedF64::from(7.3);
+        assert_eq!(value.floor_to_grid(&0.5.into(), &2.0.into()),
+                   OrderedF64::from(6.5));
+
+        let infinity = OrderedF64::from(f64::INFINITY);
+        assert_eq!(infinity.measure_to(&infinity), 0.0.into());
+        assert_eq!(infinity.offset_backward(&infinity), infinity);
+        let one = OrderedF64::from(1.0);
+        assert_eq!(one.offset_forward(&(-f64::INFINITY).into()),
+                   (-f64::INFINITY).into());
+
+        let span = FSpan::from_f64(f64::INFINITY, f64::INFINITY);
+        assert_eq!(span.measure(), 0.0.into());
+
+        // Adding 0.1 ten times gives 0.9999999999999999.
+        assert_eq!(OrderedF64::from(0.0).nth_grid_point(&0.1.into(), 10),
+                   Some(OrderedF64::from(1.0)));
+    }
+}
@@ -0,0 +1,853 @@
+/*!
+ * Conversion between spets and iCalendar (RFC 5545) text.
+ *
+ * Spans are over Unix timestamps in whole seconds (ex: `SimpleSpan<i64>`). A
+ * spet can be written as a `VFREEBUSY` component, whose `FREEBUSY` property
+ * lists each span as a `PERIOD`, or as one `VEVENT` per span. Both are
+ * wrapped in a `VCALENDAR` and use folded, CRLF-terminated lines, ready to be
+ * saved as an `.ics` file.
+ *
+ * Times must be in UTC (ex: `20260101T090000Z`) or have an explicit offset.
+ * Times that refer to a time zone by name (with a `TZID` parameter) can't be
+ * read, since that would need a time zone database.
+ */
+
+use std::fmt::{self, Display};
+use crate::span::{Span, CreatableSpan};
+use crate::vecspet::VecSpet;
+use crate::iso8601::{
+    write_datetime, FormatIso8601Error, FormatResult, Parser, ParseIso8601Error,
+    SECONDS_PER_DAY};
+
+
+const PRODID: &str = "-//spet//spet//EN";
+
+// Content lines can be at most this many octets long, not counting the CRLF.
+const MAX_LINE_OCTETS: usize = 75;
+
+
+/**
+ * The kind of time listed in a `FREEBUSY` property (its `FBTYPE` parameter).
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FreeBusyType {
+    Free,
+    Busy,
+    BusyUnavailable,
+    BusyTentative,
+}
+
+
+impl FreeBusyType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FreeBusyType::Free => "FREE",
+            FreeBusyType::Busy => "BUSY",
+            FreeBusyType::BusyUnavailable => "BUSY-UNAVAILABLE",
+            FreeBusyType::BusyTentative => "BUSY-TENTATIVE",
+        }
+    }
+
+    fn from_str(text: &str) -> Option<Self> {
+        match text.to_ascii_uppercase().as_str() {
+            "FREE" => Some(FreeBusyType::Free),
+            "BUSY" => Some(FreeBusyType::Busy),
+            "BUSY-UNAVAILABLE" => Some(FreeBusyType::BusyUnavailable),
+            "BUSY-TENTATIVE" => Some(FreeBusyType::BusyTentative),
+            _ => None,
+        }
+    }
+}
+
+
+/**
+ * Why iCalendar text couldn't be read.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ICalendarErrorKind {
+    // The content line has no `:` before its value.
+    MissingValue,
+
+    // A date, time, duration, or period was malformed.
+    InvalidValue(ParseIso8601Error),
+
+    // The time refers to a named time zone.
+    UnsupportedTimeZone,
+
+    // The `FBTYPE` parameter isn't one of the types in FreeBusyType.
+    UnknownFreeBusyType,
+
+    // The event had no `DTSTART`.
+    MissingStart,
+
+    // The event ends before it starts.
+    Reversed,
+
+    // The event's end is too far away to represent.
+    Overflow,
+
+    // An `END` didn't match the most recent `BEGIN`, or a `BEGIN` was never
+    // ended.
+    UnbalancedComponent,
+}
+
+
+/**
+ * Error from reading iCalendar text, along with the (1-based) line where the
+ * problem was found. For folded lines, this is the line the property starts
+ * on.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ParseICalendarError {
+    pub line: usize,
+    pub kind: ICalendarErrorKind,
+}
+
+
+impl Display for ParseICalendarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use ICalendarErrorKind::*;
+        write!(f, "line {}: ", self.line)?;
+        match self.kind {
+            MissingValue => write!(f, "content line has no value"),
+            InvalidValue(err) => write!(f, "invalid value ({})", err),
+            UnsupportedTimeZone =>
+                write!(f, "named time zones aren't supported"),
+            UnknownFreeBusyType => write!(f, "unknown FBTYPE"),
+            MissingStart => write!(f, "event has no DTSTART"),
+            Reversed => write!(f, "event ends before it starts"),
+            Overflow => write!(f, "event ends too far in the future"),
+            UnbalancedComponent => write!(f, "BEGIN and END don't match"),
+        }
+    }
+}
+
+
+impl std::error::Error for ParseICalendarError {}
+
+
+type ParseResult<T> = Result<T, ParseICalendarError>;
+
+
+/**
+ * Formats a spet as a calendar holding one `VFREEBUSY` component, with every
+ * span listed as a period of the type `fb_type`. `stamp` is the time the
+ * information was created (its `DTSTAMP`), usually the current time.
+ *
+ * Fails if any time is outside the years 0000 to 9999 (see
+ * iso8601::MIN_FORMATTABLE and MAX_FORMATTABLE), since iCalendar can't
+ * write it.
+ */
+pub fn format_freebusy<S: CreatableSpan<Domain = i64>>(spet: &VecSpet<S>,
+        fb_type: FreeBusyType,
+        stamp: i64) -> Result<String, FormatIso8601Error> {
+    let mut result = String::new();
+    begin_calendar(&mut result);
+    write_line(&mut result, "BEGIN:VFREEBUSY");
+    write_line(&mut result, &format!("DTSTAMP:{}", format_time(stamp)?));
+
+    let spans: Vec<&S> = spet.into_iter().collect();
+    if let (Some(first), Some(last)) = (spans.first(), spans.last()) {
+        write_line(&mut result,
+                   &format!("DTSTART:{}", format_time(*first.start())?));
+        write_line(&mut result,
+                   &format!("DTEND:{}", format_time(*last.end())?));
+
+        let periods = spans.iter()
+            .map(|span| Ok(format!("{}/{}",
+                                   format_time(*span.start())?,
+                                   format_time(*span.end())?)))
+            .collect::<FormatResult<Vec<String>>>()?;
+        write_line(&mut result, &format!(
+            "FREEBUSY;FBTYPE={}:{}", fb_type.as_str(), periods.join(",")));
+    }
+
+    write_line(&mut result, "END:VFREEBUSY");
+    write_line(&mut result, "END:VCALENDAR");
+    Ok(result)
+}
+
+
+/**
+ * Formats a spet as a calendar holding a `VEVENT` for each span, all with the
+ * same `summary`. `stamp` is used as every event's `DTSTAMP`.
+ *
+ * Fails if any time is outside the years 0000 to 9999, like
+ * format_freebusy().
+ */
+pub fn format_events<S: CreatableSpan<Domain = i64>>(spet: &VecSpet<S>,
+        summary: &str,
+        stamp: i64) -> Result<String, FormatIso8601Error> {
+    let mut result = String::new();
+    begin_calendar(&mut result);
+    for span in spet {
+        let (start, end) = (*span.start(), *span.end());
+        write_line(&mut result, "BEGIN:VEVENT");
+        write_line(&mut result, &format!("UID:{}-{}@spet", start, end));
+        write_line(&mut result, &format!("DTSTAMP:{}", format_time(stamp)?));
+        write_line(&mut result, &format!("DTSTART:{}", format_time(start)?));
+        write_line(&mut result, &format!("DTEND:{}", format_time(end)?));
+        write_line(&mut result, &format!("SUMMARY:{}", escape_text(summary)));
+        write_line(&mut result, "END:VEVENT");
+    }
+    write_line(&mut result, "END:VCALENDAR");
+    Ok(result)
+}
+
+
+/**
+ * Reads the periods of type `fb_type` from every `FREEBUSY` property in every
+ * `VFREEBUSY` component. Periods without an `FBTYPE` are `BUSY`, as in the
+ * RFC.
+ */
+pub fn parse_freebusy<S: CreatableSpan<Domain = i64>>(text: &str,
+        fb_type: FreeBusyType) -> ParseResult<VecSpet<S>> {
+    let mut spans: Vec<S> = Vec::new();
+    for_each_property(text, |components, line| {
+        if components.last().map(String::as_str) != Some("VFREEBUSY")
+                || line.name != "FREEBUSY" {
+            return Ok(());
+        }
+
+        let line_type = match line.parameter("FBTYPE") {
+            Some(name) => FreeBusyType::from_str(name).ok_or(
+                line.error(ICalendarErrorKind::UnknownFreeBusyType))?,
+            None => FreeBusyType::Busy,
+        };
+        if line_type != fb_type {
+            return Ok(());
+        }
+
+        let mut parser = Parser::new(line.value);
+        loop {
+            let (start, end) = parser.interval().map_err(
+                |err| line.error(ICalendarErrorKind::InvalidValue(err)))?;
+            spans.push(S::new(start, end));
+            if parser.is_at_end() {
+                return Ok(());
+            }
+            parser.expect(b',', "',' or end of value").map_err(
+                |err| line.error(ICalendarErrorKind::InvalidValue(err)))?;
+        }
+    })?;
+
+    spans.sort();
+    Ok(VecSpet::from_sorted_iter(spans))
+}
+
+
+/**
+ * Reads the time taken up by every `VEVENT`. Events marked
+ * `TRANSP:TRANSPARENT` don't take up time, so they're skipped.
+ *
+ * An event without a `DTEND` or `DURATION` lasts a day if its `DTSTART` is a
+ * date, otherwise it's empty. All-day events are taken to be in UTC.
+ */
+pub fn parse_events<S: CreatableSpan<Domain = i64>>(text: &str)
+        -> ParseResult<VecSpet<S>> {
+    #[derive(Default)]
+    struct Event {
+        line: usize,
+        start: Option<(i64, bool)>,
+        end: Option<i64>,
+        duration: Option<i64>,
+        transparent: bool,
+    }
+
+    let mut spans: Vec<S> = Vec::new();
+    let mut event: Option<Event> = None;
+    for_each_property(text, |components, line| {
+        let in_event = components.last().map(String::as_str) == Some("VEVENT");
+        let ends_event = line.name == "END"
+            && line.value.eq_ignore_ascii_case("VEVENT");
+        if line.name == "BEGIN" && in_event {
+            // Events can't be nested.
+            if event.is_some() {
+                return Err(line.error(
+                    ICalendarErrorKind::UnbalancedComponent));
+            }
+            event = Some(Event { line: line.number, ..Event::default() });
+            return Ok(());
+        } else if ends_event {
+            let event = event.take().ok_or_else(
+                || line.error(ICalendarErrorKind::UnbalancedComponent))?;
+            let error = |kind| ParseICalendarError { line: event.line, kind };
+            let (start, is_date) = event.start
+                .ok_or_else(|| error(ICalendarErrorKind::MissingStart))?;
+            let end = match (event.end, event.duration) {
+                (Some(end), _) => Some(end),
+                (None, Some(duration)) => start.checked_add(duration),
+                (None, None) if is_date => start.checked_add(SECONDS_PER_DAY),
+                (None, None) => Some(start),
+            }.ok_or_else(|| error(ICalendarErrorKind::Overflow))?;
+            if end < start {
+                return Err(error(ICalendarErrorKind::Reversed));
+            }
+            if !event.transparent {
+                spans.push(S::new(start, end));
+            }
+            return Ok(());
+        } else if !in_event {
+            return Ok(());
+        }
+
+        let event = event.as_mut().ok_or_else(
+            || line.error(ICalendarErrorKind::UnbalancedComponent))?;
+        match line.name.as_str() {
+            "DTSTART" => event.start = Some(line.time()?),
+            "DTEND" => event.end = Some(line.time()?.0),
+            "DURATION" => {
+                let mut parser = Parser::new(line.value);
+                let duration = parser.duration()
+                    .and_then(|duration| {
+                        parser.finish()?;
+                        Ok(duration)
+                    })
+                    .map_err(|err| line.error(
+                        ICalendarErrorKind::InvalidValue(err)))?;
+                event.duration = Some(duration);
+            },
+            "TRANSP" => {
+                event.transparent =
+                    line.value.eq_ignore_ascii_case("TRANSPARENT");
+            },
+            _ => {},
+        }
+        Ok(())
+    })?;
+
+    spans.sort();
+    Ok(VecSpet::from_sorted_iter(spans))
+}
+
+
+// An unfolded content line, ex: `DTSTART;VALUE=DATE:20260101`.
+struct ContentLine<'a> {
+    // The line it started on.
+    number: usize,
+
+    // Uppercased, since names are case-insensitive.
+    name: String,
+    parameters: Vec<(String, &'a str)>,
+    value: &'a str,
+}
+
+
+impl<'a> ContentLine<'a> {
+    fn parse(number: usize, text: &'a str) -> ParseResult<Self> {
+        let mut parameters = Vec::new();
+        let mut name: Option<&str> = None;
+        let mut parameter_start = 0;
+        let mut in_quotes = false;
+        for (i, c) in text.char_indices() {
+            match c {
+                '"' => in_quotes = !in_quotes,
+                ';' | ':' if !in_quotes => {
+                    let part = &text[parameter_start..i];
+                    match name {
+                        None => name = Some(part),
+                        Some(_) => parameters.push(split_parameter(part)),
+                    }
+                    parameter_start = i + 1;
+
+                    if c == ':' {
+                        return Ok(ContentLine {
+                            number,
+                            name: name.unwrap().to_ascii_uppercase(),
+                            parameters,
+                            value: &text[i + 1..],
+                        });
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        Err(ParseICalendarError {
+            line: number,
+            kind: ICalendarErrorKind::MissingValue,
+        })
+    }
+
+    fn parameter(&self, name: &str) -> Option<&'a str> {
+        self.parameters.iter()
+            .find(|(parameter, _)| parameter == name)
+            .map(|(_, value)| *value)
+    }
+
+    fn error(&self, kind: ICalendarErrorKind) -> ParseICalendarError {
+        ParseICalendarError { line: self.number, kind }
+    }
+
+    // The value as a date or a date and time, along with whether it was a
+    // date.
+    fn time(&self) -> ParseResult<(i64, bool)> {
+        if self.parameter("TZID").is_some() {
+            return Err(self.error(ICalendarErrorKind::UnsupportedTimeZone));
+        }
+
+        let is_date = self.parameter("VALUE")
+            .is_some_and(|value| value.eq_ignore_ascii_case("DATE"));
+        let mut parser = Parser::new(self.value);
+        let timestamp = if is_date {
+            parser.date().map(|days| days * SECONDS_PER_DAY)
+        } else {
+            parser.datetime()
+        };
+        timestamp
+            .and_then(|timestamp| {
+                parser.finish()?;
+                Ok((timestamp, is_date))
+            })
+            .map_err(|err| self.error(ICalendarErrorKind::InvalidValue(err)))
+    }
+}
+
+
+// Splits a parameter like `FBTYPE=BUSY` into its (uppercased) name and its
+// value, without any quotes around it.
+fn split_parameter(text: &str) -> (String, &str) {
+    let (name, value) = text.split_once('=').unwrap_or((text, ""));
+    let value = value.strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value);
+    (name.to_ascii_uppercase(), value)
+}
+
+
+// Unfolds `text` and calls `visit` with each content line, along with the
+// components (uppercased) that it's in. For `BEGIN` lines, the component
+// being begun is already included, and for `END` lines the component being
+// ended still is.
+fn for_each_property(text: &str,
+        mut visit: impl FnMut(&[String], &ContentLine<'_>) -> ParseResult<()>)
+        -> ParseResult<()> {
+    let mut components: Vec<String> = Vec::new();
+    let mut last_line = 0;
+    for (number, line) in unfold(text) {
+        let line = ContentLine::parse(number, &line)?;
+        last_line = number;
+
+        match line.name.as_str() {
+            "BEGIN" => {
+                components.push(line.value.to_ascii_uppercase());
+                visit(&components, &line)?;
+            },
+            "END" => {
+                if components.last() != Some(&line.value.to_ascii_uppercase()) {
+                    return Err(line.error(
+                        ICalendarErrorKind::UnbalancedComponent));
+                }
+                visit(&components, &line)?;
+                components.pop();
+            },
+            _ => visit(&components, &line)?,
+        }
+    }
+
+    if components.is_empty() {
+        Ok(())
+    } else {
+        Err(ParseICalendarError {
+            line: last_line,
+            kind: ICalendarErrorKind::UnbalancedComponent,
+        })
+    }
+}
+
+
+// Joins folded lines back together, giving each logical line along with the
+// (1-based) line it started on. Blank lines are skipped.
+fn unfold(text: &str) -> Vec<(usize, String)> {
+    let mut result: Vec<(usize, String)> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix([' ', '\t']), result.last_mut()) {
+            (Some(continuation), Some((_, previous))) => {
+                previous.push_str(continuation);
+            },
+            _ if line.is_empty() => {},
+            _ => result.push((i + 1, line.to_string())),
+        }
+    }
+    result
+}
+
+
+fn begin_calendar(out: &mut String) {
+    write_line(out, "BEGIN:VCALENDAR");
+    write_line(out, "VERSION:2.0");
+    write_line(out, &format!("PRODID:{}", PRODID));
+}
+
+
+// Writes `line`, folding it so that no line is longer than MAX_LINE_OCTETS.
+// Folds never split a UTF-8 character.
+fn write_line(out: &mut String, line: &str) {
+    let mut rest = line;
+    let mut limit = MAX_LINE_OCTETS;
+    while rest.len() > limit {
+        let mut split = limit;
+        while !rest.is_char_boundary(split) {
+            split -= 1;
+        }
+        out.push_str(&rest[..split]);
+        out.push_str("\r\n ");
+        rest = &rest[split..];
+
+        // The space that starts a continuation counts towards its length.
+        limit = MAX_LINE_OCTETS - 1;
+    }
+    out.push_str(rest);
+    out.push_str("\r\n");
+}
+
+
+// Formats a timestamp in the UTC form iCalendar uses, ex: `20260101T090000Z`.
+fn format_time(timestamp: i64) -> FormatResult<String> {
+    let mut result = String::new();
+    write_datetime(&mut result, timestamp, 0, true)?;
+    Ok(result)
+}
+
+
+fn escape_text(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                result.push('\\');
+                result.push(c);
+            },
+            '\n' => result.push_str("\\n"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::icalendar::*;
+    use crate::span::SimpleSpan;
+
+    type SSpan = SimpleSpan<i64>;
+    type Spet = VecSpet<SSpan>;
+
+    // 2026-01-01T00:00:00Z
+    const NEW_YEAR: i64 = 1_767_225_600;
+    const HOUR: i64 = 3600;
+
+    fn spet(spans: &[(i64, i64)]) -> Spet {
+        Spet::from_sorted_iter(spans.iter().map(|(start, end)| {
+            SSpan::new(NEW_YEAR + start * HOUR, NEW_YEAR + end * HOUR)
+        }))
+    }
+
+    #[test]
+    fn format_freebusy_output() {
+        let busy = spet(&[(9, 10), (13, 15)]);
+
+        assert_eq!(
+            format_freebusy(&busy, FreeBusyType::Busy, NEW_YEAR).unwrap(),
+            "BEGIN:VCALENDAR\r\n\
+             VERSION:2.0\r\n\
+             PRODID:-//spet//spet//EN\r\n\
+             BEGIN:VFREEBUSY\r\n\
+             DTSTAMP:20260101T000000Z\r\n\
+             DTSTART:20260101T090000Z\r\n\
+             DTEND:20260101T150000Z\r\n\
+             FREEBUSY;FBTYPE=BUSY:20260101T090000Z/20260101T100000Z,\
+             20260101T130000Z/202\r\n \
+             60101T150000Z\r\n\
+             END:VFREEBUSY\r\n\
+             END:VCALENDAR\r\n");
+    }
+
+    #[test]
+    fn format_events_output() {
+        let busy = spet(&[(9, 10)]);
+
+        assert_eq!(
+            format_events(&busy, "Focus time; no meetings", NEW_YEAR)
+                .unwrap(),
+            "BEGIN:VCALENDAR\r\n\
+             VERSION:2.0\r\n\
+             PRODID:-//spet//spet//EN\r\n\
+             BEGIN:VEVENT\r\n\
+             UID:1767258000-1767261600@spet\r\n\
+             DTSTAMP:20260101T000000Z\r\n\
+             DTSTART:20260101T090000Z\r\n\
+             DTEND:20260101T100000Z\r\n\
+             SUMMARY:Focus time\\; no meetings\r\n\
+             END:VEVENT\r\n\
+             END:VCALENDAR\r\n");
+    }
+
+    #[test]
+    fn format_out_of_range() {
+        use crate::iso8601::{FormatIso8601Error, MAX_FORMATTABLE};
+        let forever = Spet::from_sorted_iter(vec![
+            SSpan::new(NEW_YEAR, i64::MAX),
+        ]);
+
+        assert_eq!(format_freebusy(&forever, FreeBusyType::Busy, NEW_YEAR),
+                   Err(FormatIso8601Error::OutOfRange(i64::MAX)));
+        assert_eq!(format_events(&forever, "Busy", NEW_YEAR),
+                   Err(FormatIso8601Error::OutOfRange(i64::MAX)));
+        assert_eq!(format_freebusy(&Spet::default(), FreeBusyType::Busy,
+                                   MAX_FORMATTABLE + 1),
+                   Err(FormatIso8601Error::OutOfRange(MAX_FORMATTABLE + 1)));
+    }
+
+    mod round_trip {
+        use super::*;
+
+        #[test]
+        fn freebusy() {
+            let spans: Vec<(i64, i64)> = (0..40)
+                .map(|i| (i * 3, i * 3 + 1))
+                .collect();
+            let busy = spet(&spans);
+
+            for fb_type in [FreeBusyType::Busy, FreeBusyType::Free] {
+                let text = format_freebusy(&busy, fb_type, NEW_YEAR)
+                    .unwrap();
+                assert!(text.lines().all(|line| line.len() <= 75 + 1));
+                assert_eq!(parse_freebusy(&text, fb_type), Ok(busy.clone()));
+            }
+        }
+
+        #[test]
+        fn empty() {
+            let text = format_freebusy(&Spet::default(), FreeBusyType::Busy,
+                                       NEW_YEAR).unwrap();
+            assert_eq!(parse_freebusy(&text, FreeBusyType::Busy),
+                       Ok(Spet::default()));
+        }
+
+        #[test]
+        fn events() {
+            let busy = spet(&[(9, 10), (13, 15), (30, 31)]);
+
+            let text = format_events(&busy, "Busy", NEW_YEAR).unwrap();
+            assert_eq!(parse_events(&text), Ok(busy));
+        }
+    }
+
+    mod parse_freebusy {
+        use super::*;
+
+        #[test]
+        fn types_and_durations() {
+            let text = "BEGIN:VCALENDAR\n\
+                        BEGIN:VFREEBUSY\n\
+                        FREEBUSY:20260101T090000Z/PT1H\n\
+                        FREEBUSY;FBTYPE=FREE:20260101T100000Z/\
+                        20260101T120000Z\n\
+                        freebusy;fbtype=busy-tentative:20260101T130000Z/PT30M\n\
+                        FREEBUSY;FBTYPE=BUSY:20260101T110000+0200/PT2H\n\
+                        END:VFREEBUSY\n\
+                        END:VCALENDAR\n";
+
+            assert_eq!(parse_freebusy(text, FreeBusyType::Busy),
+                       Ok(spet(&[(9, 11)])));
+            assert_eq!(parse_freebusy(text, FreeBusyType::Free),
+                       Ok(spet(&[(10, 12)])));
+            assert_eq!(
+                parse_freebusy::<SSpan>(text, FreeBusyType::BusyTentative)
+                    .unwrap().into_iter().next().unwrap(),
+                SSpan::new(NEW_YEAR + 13 * HOUR, NEW_YEAR + 27 * HOUR / 2));
+        }
+
+        #[test]
+        fn ignores_other_components() {
+            let text = "BEGIN:VCALENDAR\r\n\
+                        BEGIN:VEVENT\r\n\
+                        FREEBUSY:20260101T090000Z/PT1H\r\n\
+                        END:VEVENT\r\n\
+                        END:VCALENDAR\r\n";
+
+            assert_eq!(parse_freebusy(text, FreeBusyType::Busy),
+                       Ok(Spet::default()));
+        }
+
+        #[test]
+        fn invalid_period() {
+            let text = "BEGIN:VFREEBUSY\r\n\
+                        FREEBUSY:20260101T090000Z/PT1H,\r\n \
+                        20260101T250000Z/PT1H\r\n\
+                        END:VFREEBUSY\r\n";
+
+            let err = parse_freebusy::<SSpan>(text, FreeBusyType::Busy)
+                .unwrap_err();
+            assert_eq!(err.line, 2);
+            assert!(matches!(err.kind, ICalendarErrorKind::InvalidValue(_)));
+        }
+
+        #[test]
+        fn unknown_type() {
+            let text = "BEGIN:VFREEBUSY\n\
+                        FREEBUSY;FBTYPE=X-OOO:20260101T090000Z/PT1H\n\
+                        END:VFREEBUSY\n";
+
+            assert_eq!(parse_freebusy::<SSpan>(text, FreeBusyType::Busy),
+                       Err(ParseICalendarError {
+                           line: 2,
+                           kind: ICalendarErrorKind::UnknownFreeBusyType,
+                       }));
+        }
+    }
+
+    mod parse_events {
+        use super::*;
+
+        fn calendar(events: &str) -> String {
+            format!("BEGIN:VCALENDAR\r\n{}END:VCALENDAR\r\n", events)
+        }
+
+        #[test]
+        fn durations_and_defaults() {
+            let text = calendar(
+                "BEGIN:VEVENT\r\n\
+                 DTSTART:20260101T090000Z\r\n\
+                 DURATION:PT90M\r\n\
+                 BEGIN:VALARM\r\n\
+                 DURATION:PT15M\r\n\
+                 END:VALARM\r\n\
+                 END:VEVENT\r\n\
+                 BEGIN:VEVENT\r\n\
+                 DTSTART;VALUE=DATE:20260103\r\n\
+                 END:VEVENT\r\n");
+
+            assert_eq!(parse_events(&text), Ok(Spet::from_sorted_iter(vec![
+                SSpan::new(NEW_YEAR + 9 * HOUR, NEW_YEAR + 21 * HOUR / 2),
+                SSpan::new(NEW_YEAR + 48 * HOUR, NEW_YEAR + 72 * HOUR),
+            ])));
+        }
+
+        #[test]
+        fn skips_transparent() {
+            let text = calendar(
+                "BEGIN:VEVENT\r\n\
+                 DTSTART:20260101T090000Z\r\n\
+                 DTEND:20260101T100000Z\r\n\
+                 TRANSP:TRANSPARENT\r\n\
+                 END:VEVENT\r\n");
+
+            assert_eq!(parse_events(&text), Ok(Spet::default()));
+        }
+
+        #[test]
+        fn overlapping_events_merge() {
+            let text = calendar(
+                "BEGIN:VEVENT\r\n\
+                 DTSTART:20260101T090000Z\r\n\
+                 DTEND:20260101T110000Z\r\n\
+                 END:VEVENT\r\n\
+                 BEGIN:VEVENT\r\n\
+                 DTSTART:20260101T100000Z\r\n\
+                 DTEND:20260101T120000Z\r\n\
+                 END:VEVENT\r\n");
+
+            assert_eq!(parse_events(&text), Ok(spet(&[(9, 12)])));
+        }
+
+        #[test]
+        fn named_time_zone() {
+            let text = calendar(
+                "BEGIN:VEVENT\r\n\
+                 DTSTART;TZID=\"Europe/Paris\":20260101T090000\r\n\
+                 END:VEVENT\r\n");
+
+            assert_eq!(parse_events::<SSpan>(&text), Err(ParseICalendarError {
+                line: 3,
+                kind: ICalendarErrorKind::UnsupportedTimeZone,
+            }));
+        }
+
+        #[test]
+        fn missing_start() {
+            let text = calendar("BEGIN:VEVENT\r\nSUMMARY:x\r\nEND:VEVENT\r\n");
+
+            assert_eq!(parse_events::<SSpan>(&text), Err(ParseICalendarError {
+                line: 2,
+                kind: ICalendarErrorKind::MissingStart,
+            }));
+        }
+
+        #[test]
+        fn reversed() {
+            let text = calendar(
+                "BEGIN:VEVENT\r\n\
+                 DTSTART:20260101T090000Z\r\n\
+                 DTEND:20260101T080000Z\r\n\
+                 END:VEVENT\r\n");
+
+            assert_eq!(parse_events::<SSpan>(&text).unwrap_err().kind,
+                       ICalendarErrorKind::Reversed);
+        }
+
+        #[test]
+        fn unbalanced() {
+            let text = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nEND:VCALENDAR\r\n";
+
+            assert_eq!(parse_events::<SSpan>(text), Err(ParseICalendarError {
+                line: 3,
+                kind: ICalendarErrorKind::UnbalancedComponent,
+            }));
+        }
+
+        #[test]
+        fn nested_events() {
+            let text = calendar(
+                "BEGIN:VEVENT\r\n\
+                 BEGIN:VEVENT\r\n\
+                 DTSTART:20260101T090000Z\r\n\
+                 END:VEVENT\r\n\
+                 SUMMARY:x\r\n\
+                 END:VEVENT\r\n");
+
+            assert_eq!(parse_events::<SSpan>(&text), Err(ParseICalendarError {
+                line: 3,
+                kind: ICalendarErrorKind::UnbalancedComponent,
+            }));
+        }
+
+        #[test]
+        fn end_overflows() {
+            let text = calendar(
+                "BEGIN:VEVENT\r\n\
+                 DTSTART:99991231T000000Z\r\n\
+                 DURATION:P106751991000000D\r\n\
+                 END:VEVENT\r\n");
+
+            assert_eq!(parse_events::<SSpan>(&text), Err(ParseICalendarError {
+                line: 2,
+                kind: ICalendarErrorKind::Overflow,
+            }));
+        }
+
+        #[test]
+        fn missing_value() {
+            let text = calendar("BEGIN:VEVENT\r\nDTSTART\r\nEND:VEVENT\r\n");
+
+            assert_eq!(parse_events::<SSpan>(&text).unwrap_err().kind,
+                       ICalendarErrorKind::MissingValue);
+        }
+    }
+
+    #[test]
+    fn folding_keeps_characters_whole() {
+        let summary = "é".repeat(60);
+        let text = format_events(&spet(&[(1, 2)]), &summary, NEW_YEAR)
+            .unwrap();
+
+        assert!(text.lines().all(|line| line.len() <= 75 + 1));
+        let unfolded: Vec<String> = unfold(&text).into_iter()
+            .map(|(_, line)| line)
+            .collect();
+        assert!(unfolded.contains(&format!("SUMMARY:{}", summary)));
+    }
+}
@@ -0,0 +1,1006 @@
+/*!
+ * Parsing and formatting of ISO 8601 time intervals.
+ *
+ * Spans are over Unix timestamps in whole seconds (ex: `SimpleSpan<i64>`).
+ * Intervals can be written as `start/end`, `start/duration`, or
+ * `duration/end`, ex: `2026-01-01T00:00:00Z/PT2H`. Times must have a UTC
+ * offset (either `Z` or a fixed offset like `+02:00`) since local times are
+ * ambiguous, and durations can't use years or months since their lengths
+ * vary. A spet is written as a comma-separated list of intervals.
+ */
+
+use std::fmt::{self, Display, Write};
+use crate::span::{Span, CreatableSpan};
+use crate::vecspet::VecSpet;
+
+
+pub(crate) const SECONDS_PER_DAY: i64 = 86_400;
+
+
+/**
+ * Why an ISO 8601 string couldn't be parsed.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Iso8601ErrorKind {
+    UnexpectedEnd,
+
+    // Something other than what's given was found.
+    Expected(&'static str),
+
+    InvalidDate,
+    InvalidTime,
+
+    // The time had no `Z` or `+hh:mm` after it.
+    MissingOffset,
+
+    // Only whole seconds are supported.
+    FractionalSeconds,
+
+    // Years and months aren't a fixed number of seconds.
+    CalendarDuration,
+
+    EmptyDuration,
+
+    // The interval ends before it starts.
+    Reversed,
+
+    Overflow,
+}
+
+
+/**
+ * Error from parsing an ISO 8601 string, along with the byte position in the
+ * string where the problem was found.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ParseIso8601Error {
+    pub position: usize,
+    pub kind: Iso8601ErrorKind,
+}
+
+
+impl Display for ParseIso8601Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Iso8601ErrorKind::*;
+        match self.kind {
+            UnexpectedEnd => write!(f, "unexpected end of input")?,
+            Expected(what) => write!(f, "expected {}", what)?,
+            InvalidDate => write!(f, "invalid date")?,
+            InvalidTime => write!(f, "invalid time")?,
+            MissingOffset =>
+                write!(f, "missing UTC offset (ex: Z or +02:00)")?,
+            FractionalSeconds =>
+                write!(f, "fractional seconds aren't supported")?,
+            CalendarDuration =>
+                write!(f, "durations in years or months aren't supported")?,
+            EmptyDuration => write!(f, "duration has no components")?,
+            Reversed => write!(f, "interval ends before it starts")?,
+            Overflow => write!(f, "value is out of range")?,
+        }
+        write!(f, " at byte {}", self.position)
+    }
+}
+
+
+impl std::error::Error for ParseIso8601Error {}
+
+
+/**
+ * Why a timestamp couldn't be formatted.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FormatIso8601Error {
+    // The (local) time is outside MIN_FORMATTABLE to MAX_FORMATTABLE, so
+    // its year can't be written in four digits.
+    OutOfRange(i64),
+
+    // The UTC offset (in seconds) isn't a whole number of minutes less than
+    // a day, so it can't be written as `+hh:mm`.
+    InvalidOffset(i32),
+}
+
+
+impl Display for FormatIso8601Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatIso8601Error::OutOfRange(timestamp) => write!(f,
+                "timestamp {} isn't in the years 0000 to 9999", timestamp),
+            FormatIso8601Error::InvalidOffset(offset) => write!(f,
+                "UTC offset of {} seconds isn't whole minutes under a day",
+                offset),
+        }
+    }
+}
+
+
+impl std::error::Error for FormatIso8601Error {}
+
+
+type ParseResult<T> = Result<T, ParseIso8601Error>;
+
+
+// Days since 1970-01-01 of the given day in the proleptic Gregorian calendar.
+// This is Howard Hinnant's days_from_civil.
+pub(crate) fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let month_from_march = (month as i64 + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100
+        + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+
+// The inverse of days_from_civil.
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = if days >= 0 { days } else { days - 146_096 } / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524
+                       - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era
+        - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    } as u32;
+    let year = year_of_era + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+
+pub(crate) struct Parser<'a> {
+    bytes: &'a [u8],
+    pub position: usize,
+}
+
+
+impl<'a> Parser<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Parser { bytes: text.as_bytes(), position: 0 }
+    }
+
+    pub fn error(&self, kind: Iso8601ErrorKind) -> ParseIso8601Error {
+        ParseIso8601Error { position: self.position, kind }
+    }
+
+    pub fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.position).copied()
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.position == self.bytes.len()
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        if self.peek() == Some(byte) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn expect(&mut self, byte: u8, what: &'static str)
+            -> ParseResult<()> {
+        match self.peek() {
+            Some(found) if found == byte => {
+                self.position += 1;
+                Ok(())
+            },
+            Some(_) => Err(self.error(Iso8601ErrorKind::Expected(what))),
+            None => Err(self.error(Iso8601ErrorKind::UnexpectedEnd)),
+        }
+    }
+
+    // Exactly `count` digits.
+    fn digits(&mut self, count: usize) -> ParseResult<i64> {
+        let mut value = 0;
+        for _ in 0..count {
+            match self.peek() {
+                Some(digit @ b'0'..=b'9') => {
+                    value = value * 10 + (digit - b'0') as i64;
+                    self.position += 1;
+                },
+                Some(_) => return Err(
+                    self.error(Iso8601ErrorKind::Expected("a digit"))),
+                None => return Err(
+                    self.error(Iso8601ErrorKind::UnexpectedEnd)),
+            }
+        }
+        Ok(value)
+    }
+
+    // One or more digits.
+    fn number(&mut self) -> ParseResult<i64> {
+        let start = self.position;
+        let mut value: i64 = 0;
+        while let Some(digit @ b'0'..=b'9') = self.peek() {
+            value = value.checked_mul(10)
+                .and_then(|value| value.checked_add((digit - b'0') as i64))
+                .ok_or(ParseIso8601Error {
+                    position: start,
+                    kind: Iso8601ErrorKind::Overflow,
+                })?;
+            self.position += 1;
+        }
+
+        if self.position == start {
+            match self.peek() {
+                Some(_) => Err(self.error(Iso8601ErrorKind::Expected(
+                    "a number"))),
+                None => Err(self.error(Iso8601ErrorKind::UnexpectedEnd)),
+            }
+        } else {
+            Ok(value)
+        }
+    }
+
+    // Rejects a fraction (ex: `.5`) unless it's all zeros.
+    fn whole_seconds(&mut self) -> ParseResult<()> {
+        if self.peek() == Some(b'.') || self.peek() == Some(b',') {
+            let start = self.position;
+            self.position += 1;
+            let mut any_digits = false;
+            while let Some(digit @ b'0'..=b'9') = self.peek() {
+                any_digits = true;
+                if digit != b'0' {
+                    return Err(ParseIso8601Error {
+                        position: start,
+                        kind: Iso8601ErrorKind::FractionalSeconds,
+                    });
+                }
+                self.position += 1;
+            }
+            if !any_digits {
+                return Err(self.error(Iso8601ErrorKind::Expected("a digit")));
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * A date and time with a UTC offset, in either the extended format
+     * (`2026-01-01T08:00:00+02:00`) or the basic format
+     * (`20260101T080000+0200`). Gives a Unix timestamp.
+     */
+    pub fn datetime(&mut self) -> ParseResult<i64> {
+        let (days, extended) = self.calendar_date()?;
+
+        self.expect(b'T', "'T'")?;
+        let time_start = self.position;
+        let hour = self.digits(2)?;
+        if extended {
+            self.expect(b':', "':'")?;
+        }
+        let minute = self.digits(2)?;
+        if extended {
+            self.expect(b':', "':'")?;
+        }
+        let second = self.digits(2)?;
+        if hour > 23 || minute > 59 || second > 59 {
+            return Err(ParseIso8601Error {
+                position: time_start,
+                kind: Iso8601ErrorKind::InvalidTime,
+            });
+        }
+        self.whole_seconds()?;
+
+        let offset = self.offset(extended)?;
+        Ok(days * SECONDS_PER_DAY + hour * 3600 + minute * 60 + second
+           - offset)
+    }
+
+    /**
+     * A date without a time, in either the extended format (`2026-01-01`) or
+     * the basic format (`20260101`). Gives the number of days since
+     * 1970-01-01.
+     */
+    pub fn date(&mut self) -> ParseResult<i64> {
+        Ok(self.calendar_date()?.0)
+    }
+
+    // Days since 1970-01-01, and whether the date was in the extended format.
+    fn calendar_date(&mut self) -> ParseResult<(i64, bool)> {
+        let start = self.position;
+        let year = self.digits(4)?;
+        let extended = self.eat(b'-');
+        let month = self.digits(2)? as u32;
+        if extended {
+            self.expect(b'-', "'-'")?;
+        }
+        let day = self.digits(2)? as u32;
+        if !(1..=12).contains(&month) || day < 1
+                || day > days_in_month(year, month) {
+            return Err(ParseIso8601Error {
+                position: start,
+                kind: Iso8601ErrorKind::InvalidDate,
+            });
+        }
+
+        Ok((days_from_civil(year, month, day), extended))
+    }
+
+    fn offset(&mut self, extended: bool) -> ParseResult<i64> {
+        let sign = match self.peek() {
+            Some(b'Z') => {
+                self.position += 1;
+                return Ok(0);
+            },
+            Some(b'+') => 1,
+            Some(b'-') => -1,
+            _ => return Err(self.error(Iso8601ErrorKind::MissingOffset)),
+        };
+        self.position += 1;
+
+        let start = self.position;
+        let hours = self.digits(2)?;
+        let minutes = match self.peek() {
+            Some(b':') if extended => {
+                self.position += 1;
+                self.digits(2)?
+            },
+            Some(b'0'..=b'9') if !extended => self.digits(2)?,
+            _ => 0,
+        };
+        if hours > 23 || minutes > 59 {
+            return Err(ParseIso8601Error {
+                position: start,
+                kind: Iso8601ErrorKind::InvalidTime,
+            });
+        }
+
+        Ok(sign * (hours * 3600 + minutes * 60))
+    }
+
+    /**
+     * A duration like `P1DT2H30M` or `P2W`, in seconds.
+     */
+    pub fn duration(&mut self) -> ParseResult<i64> {
+        let start = self.position;
+        self.expect(b'P', "'P'")?;
+
+        let mut seconds: i64 = 0;
+        let mut any_components = false;
+        let mut in_time = false;
+        loop {
+            if !in_time && self.eat(b'T') {
+                in_time = true;
+                continue;
+            }
+            match self.peek() {
+                Some(b'0'..=b'9') => {},
+                _ => break,
+            }
+
+            let component_start = self.position;
+            let value = self.number()?;
+            self.whole_seconds()?;
+            let unit = match (in_time, self.peek()) {
+                (false, Some(b'W')) => 7 * SECONDS_PER_DAY,
+                (false, Some(b'D')) => SECONDS_PER_DAY,
+                (true, Some(b'H')) => 3600,
+                (true, Some(b'M')) => 60,
+                (true, Some(b'S')) => 1,
+                (false, Some(b'Y')) | (false, Some(b'M')) => {
+                    return Err(ParseIso8601Error {
+                        position: component_start,
+                        kind: Iso8601ErrorKind::CalendarDuration,
+                    });
+                },
+                (_, Some(_)) => return Err(self.error(
+                    Iso8601ErrorKind::Expected("a duration designator"))),
+                (_, None) => return Err(
+                    self.error(Iso8601ErrorKind::UnexpectedEnd)),
+            };
+            self.position += 1;
+
+            seconds = value.checked_mul(unit)
+                .and_then(|value| seconds.checked_add(value))
+                .ok_or(ParseIso8601Error {
+                    position: component_start,
+                    kind: Iso8601ErrorKind::Overflow,
+                })?;
+            any_components = true;
+        }
+
+        if any_components {
+            Ok(seconds)
+        } else {
+            Err(ParseIso8601Error {
+                position: start,
+                kind: Iso8601ErrorKind::EmptyDuration,
+            })
+        }
+    }
+
+    /**
+     * An interval as `start/end`, `start/duration`, or `duration/end`. Gives
+     * the start and end as Unix timestamps.
+     */
+    pub fn interval(&mut self) -> ParseResult<(i64, i64)> {
+        let start_position = self.position;
+        let overflow = ParseIso8601Error {
+            position: start_position,
+            kind: Iso8601ErrorKind::Overflow,
+        };
+
+        let (start, end) = if self.peek() == Some(b'P') {
+            let duration = self.duration()?;
+            self.expect(b'/', "'/'")?;
+            let end = self.datetime()?;
+            (end.checked_sub(duration).ok_or(overflow)?, end)
+        } else {
+            let start = self.datetime()?;
+            self.expect(b'/', "'/'")?;
+            if self.peek() == Some(b'P') {
+                let duration = self.duration()?;
+                (start, start.checked_add(duration).ok_or(overflow)?)
+            } else {
+                (start, self.datetime()?)
+            }
+        };
+
+        if start > end {
+            return Err(ParseIso8601Error {
+                position: start_position,
+                kind: Iso8601ErrorKind::Reversed,
+            });
+        }
+        Ok((start, end))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') = self.peek() {
+            self.position += 1;
+        }
+    }
+
+    pub fn finish(&self) -> ParseResult<()> {
+        if self.is_at_end() {
+            Ok(())
+        } else {
+            Err(self.error(Iso8601ErrorKind::Expected("end of input")))
+        }
+    }
+}
+
+
+/**
+ * Parses a date and time like `2026-01-01T08:00:00+02:00` into a Unix
+ * timestamp.
+ */
+pub fn parse_datetime(text: &str) -> Result<i64, ParseIso8601Error> {
+    let mut parser = Parser::new(text);
+    let timestamp = parser.datetime()?;
+    parser.finish()?;
+    Ok(timestamp)
+}
+
+
+/**
+ * Parses a duration like `P1DT2H` into a number of seconds.
+ */
+pub fn parse_duration(text: &str) -> Result<i64, ParseIso8601Error> {
+    let mut parser = Parser::new(text);
+    let seconds = parser.duration()?;
+    parser.finish()?;
+    Ok(seconds)
+}
+
+
+/**
+ * Parses an interval like `2026-01-01T00:00:00Z/PT8H` into a span.
+ */
+pub fn parse_interval<S: CreatableSpan<Domain = i64>>(text: &str)
+        -> Result<S, ParseIso8601Error> {
+    let mut parser = Parser::new(text);
+    let (start, end) = parser.interval()?;
+    parser.finish()?;
+    Ok(S::new(start, end))
+}
+
+
+/**
+ * Parses a comma-separated list of intervals into a spet. The intervals may
+ * be in any order and may overlap.
+ */
+pub fn parse_spet<S: CreatableSpan<Domain = i64>>(text: &str)
+        -> Result<VecSpet<S>, ParseIso8601Error> {
+    let mut parser = Parser::new(text);
+    let mut spans: Vec<S> = Vec::new();
+
+    parser.skip_whitespace();
+    if !parser.is_at_end() {
+        loop {
+            let (start, end) = parser.interval()?;
+            spans.push(S::new(start, end));
+
+            parser.skip_whitespace();
+            if parser.is_at_end() {
+                break;
+            }
+            parser.expect(b',', "',' or end of input")?;
+            parser.skip_whitespace();
+        }
+    }
+
+    spans.sort();
+    Ok(VecSpet::from_sorted_iter(spans))
+}
+
+
+/**
+ * The earliest timestamp that can be formatted, `0000-01-01T00:00:00Z`.
+ * Dates need four-digit years to be parsed back, so only the years 0000 to
+ * 9999 can be formatted.
+ */
+pub const MIN_FORMATTABLE: i64 = -62_167_219_200;
+
+
+/**
+ * The latest timestamp that can be formatted, `9999-12-31T23:59:59Z`.
+ */
+pub const MAX_FORMATTABLE: i64 = 253_402_300_799;
+
+
+pub(crate) type FormatResult<T> = Result<T, FormatIso8601Error>;
+
+
+// Writes the local time at `offset` in the extended format, or in the basic
+// format (ex: `20260101T090000Z`, as iCalendar uses) if `basic` is true.
+pub(crate) fn write_datetime(out: &mut String,
+        timestamp: i64,
+        offset: i32,
+        basic: bool) -> FormatResult<()> {
+    if offset % 60 != 0 || offset.abs() >= SECONDS_PER_DAY as i32 {
+        return Err(FormatIso8601Error::InvalidOffset(offset));
+    }
+    let local = timestamp.checked_add(offset as i64)
+        .filter(|local| (MIN_FORMATTABLE..=MAX_FORMATTABLE).contains(local))
+        .ok_or(FormatIso8601Error::OutOfRange(timestamp))?;
+
+    let (year, month, day) = civil_from_days(local.div_euclid(SECONDS_PER_DAY));
+    let seconds = local.rem_euclid(SECONDS_PER_DAY);
+    let (date_separator, time_separator) =
+        if basic { ("", "") } else { ("-", ":") };
+    write!(out, "{:04}{}{:02}{}{:02}T{:02}{}{:02}{}{:02}",
+           year, date_separator, month, date_separator, day,
+           seconds / 3600, time_separator, seconds % 3600 / 60,
+           time_separator, seconds % 60).unwrap();
+
+    if offset == 0 {
+        out.push('Z');
+    } else {
+        let sign = if offset < 0 { '-' } else { '+' };
+        let offset = offset.abs();
+        write!(out, "{}{:02}{}{:02}", sign,
+               offset / 3600, time_separator, offset % 3600 / 60).unwrap();
+    }
+    Ok(())
+}
+
+
+/**
+ * Formats a Unix timestamp like `2026-01-01T08:00:00Z`.
+ *
+ * Fails if the timestamp is outside MIN_FORMATTABLE to MAX_FORMATTABLE.
+ */
+pub fn format_datetime(timestamp: i64) -> FormatResult<String> {
+    format_datetime_with_offset(timestamp, 0)
+}
+
+
+/**
+ * Formats a Unix timestamp as the local time at a fixed UTC offset (given in
+ * seconds), like `2026-01-01T10:00:00+02:00`. An offset of zero is written as
+ * `Z`.
+ *
+ * Fails if the local time is outside MIN_FORMATTABLE to MAX_FORMATTABLE, or
+ * if the offset isn't a whole number of minutes less than a day (since
+ * anything else couldn't be parsed back).
+ */
+pub fn format_datetime_with_offset(timestamp: i64, offset: i32)
+        -> FormatResult<String> {
+    let mut result = String::new();
+    write_datetime(&mut result, timestamp, offset, false)?;
+    Ok(result)
+}
+
+
+/**
+ * Formats a number of seconds like `P1DT2H30M`.
+ */
+pub fn format_duration(seconds: i64) -> String {
+    assert!(seconds >= 0);
+    if seconds == 0 {
+        return "PT0S".to_string();
+    }
+
+    let mut result = "P".to_string();
+    let days = seconds / SECONDS_PER_DAY;
+    if days > 0 {
+        write!(result, "{}D", days).unwrap();
+    }
+
+    let seconds = seconds % SECONDS_PER_DAY;
+    if seconds > 0 {
+        result.push('T');
+        let parts = [
+            (seconds / 3600, 'H'),
+            (seconds % 3600 / 60, 'M'),
+            (seconds % 60, 'S'),
+        ];
+        for (count, designator) in parts.iter() {
+            if *count > 0 {
+                write!(result, "{}{}", count, designator).unwrap();
+            }
+        }
+    }
+    result
+}
+
+
+/**
+ * Formats a span like `2026-01-01T00:00:00Z/2026-01-01T08:00:00Z`. Fails if
+ * either end isn't formattable, like format_datetime().
+ */
+pub fn format_interval<S: Span<Domain = i64>>(span: &S)
+        -> FormatResult<String> {
+    format_interval_with_offset(span, 0)
+}
+
+
+/**
+ * Formats a span with both ends as local times at a fixed UTC offset (given
+ * in seconds). Fails if either isn't formattable, like
+ * format_datetime_with_offset().
+ */
+pub fn format_interval_with_offset<S: Span<Domain = i64>>(
+        span: &S, offset: i32) -> FormatResult<String> {
+    let mut result = String::new();
+    write_datetime(&mut result, *span.start(), offset, false)?;
+    result.push('/');
+    write_datetime(&mut result, *span.end(), offset, false)?;
+    Ok(result)
+}
+
+
+/**
+ * Formats a spet as a comma-separated list of intervals. Fails if any span
+ * isn't formattable (ex: one that ends at i64::MAX to mean "forever"), like
+ * format_interval().
+ */
+pub fn format_spet<S: CreatableSpan<Domain = i64>>(spet: &VecSpet<S>)
+        -> FormatResult<String> {
+    let intervals = spet.into_iter()
+        .map(format_interval)
+        .collect::<FormatResult<Vec<_>>>()?;
+    Ok(intervals.join(","))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::iso8601::*;
+    use crate::span::SimpleSpan;
+
+    type SSpan = SimpleSpan<i64>;
+
+    // 2026-01-01T00:00:00Z
+    const NEW_YEAR: i64 = 1_767_225_600;
+
+    mod datetime {
+        use super::*;
+
+        #[test]
+        fn utc() {
+            assert_eq!(parse_datetime("2026-01-01T00:00:00Z"), Ok(NEW_YEAR));
+            assert_eq!(parse_datetime("1970-01-01T00:00:00Z"), Ok(0));
+        }
+
+        #[test]
+        fn fixed_offsets() {
+            assert_eq!(parse_datetime("2026-01-01T02:00:00+02:00"),
+                       Ok(NEW_YEAR));
+            assert_eq!(parse_datetime("2025-12-31T19:30:00-04:30"),
+                       Ok(NEW_YEAR));
+            assert_eq!(parse_datetime("2026-01-01T02:00:00+02"),
+                       Ok(NEW_YEAR));
+        }
+
+        #[test]
+        fn basic_format() {
+            assert_eq!(parse_datetime("20260101T020000+0200"), Ok(NEW_YEAR));
+            assert_eq!(parse_datetime("20260101T000000Z"), Ok(NEW_YEAR));
+        }
+
+        #[test]
+        fn zero_fraction() {
+            assert_eq!(parse_datetime("2026-01-01T00:00:00.000Z"),
+                       Ok(NEW_YEAR));
+        }
+
+        #[test]
+        fn before_epoch() {
+            assert_eq!(parse_datetime("1969-12-31T23:59:59Z"), Ok(-1));
+        }
+
+        #[test]
+        fn leap_day() {
+            assert_eq!(parse_datetime("2024-02-29T00:00:00Z"),
+                       Ok(1_709_164_800));
+            assert_eq!(parse_datetime("2023-02-29T00:00:00Z").unwrap_err(),
+                       ParseIso8601Error {
+                           position: 0,
+                           kind: Iso8601ErrorKind::InvalidDate,
+                       });
+        }
+
+        #[test]
+        fn format() {
+            assert_eq!(format_datetime(NEW_YEAR).unwrap(),
+                       "2026-01-01T00:00:00Z");
+            assert_eq!(format_datetime(-1).unwrap(), "1969-12-31T23:59:59Z");
+            assert_eq!(format_datetime_with_offset(NEW_YEAR, -16_200).unwrap(),
+                       "2025-12-31T19:30:00-04:30");
+        }
+
+        #[test]
+        fn formattable_range() {
+            let earliest = format_datetime(MIN_FORMATTABLE).unwrap();
+            assert_eq!(earliest, "0000-01-01T00:00:00Z");
+            assert_eq!(parse_datetime(&earliest), Ok(MIN_FORMATTABLE));
+
+            let latest = format_datetime(MAX_FORMATTABLE).unwrap();
+            assert_eq!(latest, "9999-12-31T23:59:59Z");
+            assert_eq!(parse_datetime(&latest), Ok(MAX_FORMATTABLE));
+        }
+
+        #[test]
+        fn year_after_9999() {
+            assert_eq!(format_datetime(MAX_FORMATTABLE + 1),
+                       Err(FormatIso8601Error::OutOfRange(
+                           MAX_FORMATTABLE + 1)));
+            assert_eq!(format_datetime_with_offset(MAX_FORMATTABLE, 60),
+                       Err(FormatIso8601Error::OutOfRange(MAX_FORMATTABLE)));
+        }
+
+        #[test]
+        fn offset_overflows() {
+            assert_eq!(format_datetime_with_offset(i64::MAX, 3600),
+                       Err(FormatIso8601Error::OutOfRange(i64::MAX)));
+        }
+
+        #[test]
+        fn invalid_offsets() {
+            use FormatIso8601Error::InvalidOffset;
+
+            assert_eq!(format_datetime_with_offset(NEW_YEAR, 90_000),
+                       Err(InvalidOffset(90_000)));
+            assert_eq!(format_datetime_with_offset(NEW_YEAR, -86_400),
+                       Err(InvalidOffset(-86_400)));
+            assert_eq!(format_datetime_with_offset(NEW_YEAR, 3661),
+                       Err(InvalidOffset(3661)));
+        }
+
+        #[test]
+        fn offsets_round_trip() {
+            for offset in [-86_340, -16_200, 60, 3600, 86_340].iter() {
+                let text = format_datetime_with_offset(NEW_YEAR, *offset)
+                    .unwrap();
+                assert_eq!(parse_datetime(&text), Ok(NEW_YEAR));
+            }
+        }
+
+        #[test]
+        fn round_trips_across_years() {
+            let mut timestamp = -5_000_000_000;
+            while timestamp < 5_000_000_000 {
+                let text = format_datetime(timestamp).unwrap();
+                assert_eq!(parse_datetime(&text), Ok(timestamp));
+                timestamp += 7_777_777;
+            }
+        }
+
+        #[test]
+        fn errors() {
+            use Iso8601ErrorKind::*;
+            let error = |text| parse_datetime(text).unwrap_err();
+
+            assert_eq!(error("2026-01-01T00:00:00"),
+                       ParseIso8601Error { position: 19, kind: MissingOffset });
+            assert_eq!(error("2026-01-01 00:00:00Z"),
+                       ParseIso8601Error { position: 10,
+                                           kind: Expected("'T'") });
+            assert_eq!(error("2026-01-01T24:00:00Z"),
+                       ParseIso8601Error { position: 11, kind: InvalidTime });
+            assert_eq!(error("2026-13-01T00:00:00Z"),
+                       ParseIso8601Error { position: 0, kind: InvalidDate });
+            assert_eq!(error("2026-01-01T00:00:00.5Z"),
+                       ParseIso8601Error { position: 19,
+                                           kind: FractionalSeconds });
+            assert_eq!(error("2026-01-01T00:00"),
+                       ParseIso8601Error { position: 16,
+                                           kind: UnexpectedEnd });
+            assert_eq!(error("2026-01-01T00:00:00Zjunk"),
+                       ParseIso8601Error { position: 20,
+                                           kind: Expected("end of input") });
+        }
+    }
+
+    mod duration {
+        use super::*;
+
+        #[test]
+        fn parse() {
+            assert_eq!(parse_duration("PT2H"), Ok(7200));
+            assert_eq!(parse_duration("P1DT2H30M15S"), Ok(95_415));
+            assert_eq!(parse_duration("P2W"), Ok(1_209_600));
+            assert_eq!(parse_duration("PT90M"), Ok(5400));
+        }
+
+        #[test]
+        fn format() {
+            assert_eq!(format_duration(7200), "PT2H");
+            assert_eq!(format_duration(95_415), "P1DT2H30M15S");
+            assert_eq!(format_duration(86_400), "P1D");
+            assert_eq!(format_duration(0), "PT0S");
+        }
+
+        #[test]
+        fn errors() {
+            use Iso8601ErrorKind::*;
+            let error = |text| parse_duration(text).unwrap_err();
+
+            assert_eq!(error("P1M"),
+                       ParseIso8601Error { position: 1,
+                                           kind: CalendarDuration });
+            assert_eq!(error("P1Y2D"),
+                       ParseIso8601Error { position: 1,
+                                           kind: CalendarDuration });
+            assert_eq!(error("PT"),
+                       ParseIso8601Error { position: 0, kind: EmptyDuration });
+            assert_eq!(error("PT2X"),
+                       ParseIso8601Error {
+                           position: 3,
+                           kind: Expected("a duration designator"),
+                       });
+            assert_eq!(error("PT99999999999999999999S"),
+                       ParseIso8601Error { position: 2, kind: Overflow });
+        }
+    }
+
+    mod interval {
+        use super::*;
+
+        #[test]
+        fn start_and_end() {
+            assert_eq!(
+                parse_interval("2026-01-01T00:00:00Z/2026-01-01T08:00:00Z"),
+                Ok(SSpan::new(NEW_YEAR, NEW_YEAR + 8 * 3600)));
+        }
+
+        #[test]
+        fn start_and_duration() {
+            assert_eq!(parse_interval("2026-01-01T00:00:00Z/PT2H"),
+                       Ok(SSpan::new(NEW_YEAR, NEW_YEAR + 7200)));
+        }
+
+        #[test]
+        fn duration_and_end() {
+            assert_eq!(parse_interval("PT2H/2026-01-01T02:00:00Z"),
+                       Ok(SSpan::new(NEW_YEAR, NEW_YEAR + 7200)));
+        }
+
+        #[test]
+        fn mixed_offsets() {
+            assert_eq!(
+                parse_interval("2026-01-01T00:00:00Z/2026-01-01T03:00:00+01:00"),
+                Ok(SSpan::new(NEW_YEAR, NEW_YEAR + 7200)));
+        }
+
+        #[test]
+        fn format() {
+            let span = SSpan::new(NEW_YEAR, NEW_YEAR + 7200);
+
+            assert_eq!(format_interval(&span).unwrap(),
+                       "2026-01-01T00:00:00Z/2026-01-01T02:00:00Z");
+            assert_eq!(format_interval_with_offset(&span, 3600).unwrap(),
+                       "2026-01-01T01:00:00+01:00/2026-01-01T03:00:00+01:00");
+        }
+
+        #[test]
+        fn format_out_of_range() {
+            assert_eq!(format_interval(&SSpan::new(NEW_YEAR, i64::MAX)),
+                       Err(FormatIso8601Error::OutOfRange(i64::MAX)));
+        }
+
+        #[test]
+        fn errors() {
+            use Iso8601ErrorKind::*;
+            let error = |text| parse_interval::<SSpan>(text).unwrap_err();
+
+            assert_eq!(error("2026-01-01T00:00:00Z"),
+                       ParseIso8601Error { position: 20, kind: UnexpectedEnd });
+            assert_eq!(error("2026-01-02T00:00:00Z/2026-01-01T00:00:00Z"),
+                       ParseIso8601Error { position: 0, kind: Reversed });
+            assert_eq!(error("PT1H/PT2H"),
+                       ParseIso8601Error { position: 5,
+                                           kind: Expected("a digit") });
+        }
+    }
+
+    mod spet {
+        use super::*;
+        use crate::vecspet::VecSpet;
+
+        #[test]
+        fn parse() {
+            let spet: VecSpet<SSpan> = parse_spet(
+                "2026-01-01T04:00:00Z/PT1H, 2026-01-01T00:00:00Z/PT2H")
+                .unwrap();
+
+            assert_eq!(spet, VecSpet::from_sorted_iter(vec![
+                SSpan::new(NEW_YEAR, NEW_YEAR + 7200),
+                SSpan::new(NEW_YEAR + 4 * 3600, NEW_YEAR + 5 * 3600),
+            ]));
+        }
+
+        #[test]
+        fn empty() {
+            let spet: VecSpet<SSpan> = parse_spet("").unwrap();
+
+            assert!(spet.is_empty());
+        }
+
+        #[test]
+        fn round_trip() {
+            let spet: VecSpet<SSpan> = VecSpet::from_sorted_iter(vec![
+                SSpan::new(NEW_YEAR, NEW_YEAR + 7200),
+                SSpan::new(NEW_YEAR + 4 * 3600, NEW_YEAR + 5 * 3600),
+            ]);
+
+            let text = format_spet(&spet).unwrap();
+            assert_eq!(text, "2026-01-01T00:00:00Z/2026-01-01T02:00:00Z,\
+                              2026-01-01T04:00:00Z/2026-01-01T05:00:00Z");
+            assert_eq!(parse_spet(&text), Ok(spet));
+        }
+
+        #[test]
+        fn forever() {
+            let spet: VecSpet<SSpan> = VecSpet::from_sorted_iter(vec![
+                SSpan::new(0, i64::MAX),
+            ]);
+
+            assert_eq!(format_spet(&spet),
+                       Err(FormatIso8601Error::OutOfRange(i64::MAX)));
+        }
+
+        #[test]
+        fn error_position_in_list() {
+            let result = parse_spet::<SSpan>(
+                "2026-01-01T00:00:00Z/PT2H,2026-01-01T00:00:00Z/PT2Q");
+
+            assert_eq!(result.unwrap_err(), ParseIso8601Error {
+                position: 50,
+                kind: Iso8601ErrorKind::Expected("a duration designator"),
+            });
+        }
+    }
+}
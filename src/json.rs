@@ -0,0 +1,941 @@
+/*!
+ * Reading and writing spets as JSON.
+ *
+ * A spet is written as an array of spans, where each span is either a
+ * `[start, end]` pair or a `{"start": start, "end": end}` object. Ex:
+ * `[[1, 5], [7, 9]]`. Spets keyed by name are written as an object whose
+ * values are spets, ex: `{"alice": [[1, 5]], "bob": [[3, 8]]}`.
+ *
+ * Bounds can be integers, OrderedF64s, or Strings (see JsonDomain).
+ */
+
+use std::collections::HashMap;
+use std::fmt::{self, Display, Write};
+use crate::span::CreatableSpan;
+use crate::vecspet::VecSpet;
+use crate::float::OrderedF64;
+
+
+/**
+ * A bound as it appeared in JSON.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonScalar {
+    // The number's text, exactly as written (ex: `1.5e3`).
+    Number(String),
+
+    // The string's contents, with escapes already decoded.
+    String(String),
+}
+
+
+/**
+ * A domain whose values can be written to and read from JSON.
+ */
+pub trait JsonDomain: Sized {
+    fn write_json(&self, out: &mut String);
+
+    // Returns None if `value` can't be converted to this domain.
+    fn from_json(value: &JsonScalar) -> Option<Self>;
+}
+
+
+macro_rules! impl_json_domain_for_integers {
+    ($($t:ty),*) => {
+        $(
+            impl JsonDomain for $t {
+                fn write_json(&self, out: &mut String) {
+                    write!(out, "{}", self).unwrap();
+                }
+
+                fn from_json(value: &JsonScalar) -> Option<Self> {
+                    match value {
+                        JsonScalar::Number(text) => text.parse().ok(),
+                        JsonScalar::String(_) => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_json_domain_for_integers!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+
+/**
+ * JSON has no infinity, so infinite values are written as `1e999` and
+ * `-1e999`. These parse back to infinity here and in JavaScript.
+ */
+impl JsonDomain for OrderedF64 {
+    fn write_json(&self, out: &mut String) {
+        let value = self.get();
+        if value == f64::INFINITY {
+            out.push_str("1e999");
+        } else if value == f64::NEG_INFINITY {
+            out.push_str("-1e999");
+        } else {
+            write!(out, "{}", value).unwrap();
+        }
+    }
+
+    fn from_json(value: &JsonScalar) -> Option<Self> {
+        match value {
+            JsonScalar::Number(text) => text.parse::<f64>().ok()
+                .and_then(OrderedF64::new),
+            JsonScalar::String(_) => None,
+        }
+    }
+}
+
+
+impl JsonDomain for String {
+    fn write_json(&self, out: &mut String) {
+        write_string(out, self);
+    }
+
+    fn from_json(value: &JsonScalar) -> Option<Self> {
+        match value {
+            JsonScalar::String(text) => Some(text.clone()),
+            JsonScalar::Number(_) => None,
+        }
+    }
+}
+
+
+/**
+ * How each span is written.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum JsonStyle {
+    // `[1, 5]`
+    Pairs,
+
+    // `{"start": 1, "end": 5}`
+    Objects,
+}
+
+
+/**
+ * Why JSON couldn't be parsed into a spet.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum JsonErrorKind {
+    UnexpectedEnd,
+
+    // Something other than what's given was found.
+    Expected(&'static str),
+
+    InvalidNumber,
+
+    // The string had a bad escape or an unescaped control character.
+    InvalidString,
+
+    // The bound was valid JSON but not a value of the spet's domain.
+    InvalidBound,
+
+    // The span object had no member with the given name.
+    MissingBound(&'static str),
+
+    // The object had the same key twice.
+    DuplicateKey,
+
+    // The span ends before it starts.
+    Reversed,
+
+    // There was more than whitespace after the spet.
+    TrailingCharacters,
+
+    // A skipped value had arrays or objects nested more than
+    // MAX_SKIPPED_DEPTH deep.
+    TooDeep,
+}
+
+
+/**
+ * Error from parsing JSON, along with the (1-based) line and column where the
+ * problem was found. Columns count characters, not bytes.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ParseJsonError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: JsonErrorKind,
+}
+
+
+impl Display for ParseJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use JsonErrorKind::*;
+        match self.kind {
+            UnexpectedEnd => write!(f, "unexpected end of input")?,
+            Expected(what) => write!(f, "expected {}", what)?,
+            InvalidNumber => write!(f, "invalid number")?,
+            InvalidString => write!(f, "invalid string")?,
+            InvalidBound => write!(f, "bound is the wrong type")?,
+            MissingBound(name) => write!(f, "span has no {:?}", name)?,
+            DuplicateKey => write!(f, "duplicate key")?,
+            Reversed => write!(f, "span ends before it starts")?,
+            TrailingCharacters => write!(f, "unexpected trailing characters")?,
+            TooDeep => write!(f, "value is nested too deeply")?,
+        }
+        write!(f, " at line {}, column {}", self.line, self.column)
+    }
+}
+
+
+impl std::error::Error for ParseJsonError {}
+
+
+type ParseResult<T> = Result<T, ParseJsonError>;
+
+
+pub fn format_spet<S: CreatableSpan>(spet: &VecSpet<S>, style: JsonStyle)
+        -> String
+        where S::Domain: JsonDomain {
+    let mut result = String::new();
+    write_spet(&mut result, spet, style);
+    result
+}
+
+
+/**
+ * Formats spets as an object keyed by name. Keys are written in ascending
+ * order.
+ */
+pub fn format_keyed<K, S>(spets: &HashMap<K, VecSpet<S>>, style: JsonStyle)
+        -> String
+        where K: AsRef<str> + Ord,
+              S: CreatableSpan,
+              S::Domain: JsonDomain {
+    let mut entries: Vec<(&K, &VecSpet<S>)> = spets.iter().collect();
+    entries.sort_by_key(|(key, _)| *key);
+
+    let mut result = String::from("{");
+    for (i, (key, spet)) in entries.into_iter().enumerate() {
+        if i > 0 {
+            result.push(',');
+        }
+        write_string(&mut result, key.as_ref());
+        result.push(':');
+        write_spet(&mut result, spet, style);
+    }
+    result.push('}');
+    result
+}
+
+
+/**
+ * Parses a spet written in either style (they can be mixed). The spans may be
+ * in any order and may overlap.
+ */
+pub fn parse_spet<S: CreatableSpan>(text: &str) -> ParseResult<VecSpet<S>>
+        where S::Domain: JsonDomain {
+    let mut parser = Parser::new(text);
+    let spet = parser.spet()?;
+    parser.end()?;
+    Ok(spet)
+}
+
+
+/**
+ * Parses an object of spets keyed by name.
+ */
+pub fn parse_keyed<S: CreatableSpan>(text: &str)
+        -> ParseResult<HashMap<String, VecSpet<S>>>
+        where S::Domain: JsonDomain {
+    let mut parser = Parser::new(text);
+    let mut result = HashMap::new();
+    parser.object(|parser, key, key_position| {
+        let spet = parser.spet()?;
+        if result.insert(key, spet).is_some() {
+            return Err(parser.error_at(key_position,
+                                       JsonErrorKind::DuplicateKey));
+        }
+        Ok(())
+    })?;
+    parser.end()?;
+    Ok(result)
+}
+
+
+fn write_spet<S: CreatableSpan>(out: &mut String,
+        spet: &VecSpet<S>,
+        style: JsonStyle)
+        where S::Domain: JsonDomain {
+    out.push('[');
+    for (i, span) in spet.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        match style {
+            JsonStyle::Pairs => {
+                out.push('[');
+                span.start().write_json(out);
+                out.push(',');
+                span.end().write_json(out);
+                out.push(']');
+            },
+            JsonStyle::Objects => {
+                out.push_str("{\"start\":");
+                span.start().write_json(out);
+                out.push_str(",\"end\":");
+                span.end().write_json(out);
+                out.push('}');
+            },
+        }
+    }
+    out.push(']');
+}
+
+
+fn write_string(out: &mut String, text: &str) {
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                write!(out, "\\u{:04x}", c as u32).unwrap();
+            },
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+
+/**
+ * How deeply arrays and objects can be nested in a value that's skipped over
+ * (ex: an unknown member of a span object), so that untrusted input can't
+ * overflow the stack.
+ */
+pub const MAX_SKIPPED_DEPTH: usize = 128;
+
+
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Parser { text, position: 0 }
+    }
+
+    fn error_at(&self, position: usize, kind: JsonErrorKind)
+            -> ParseJsonError {
+        let before = &self.text[..position];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        ParseJsonError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            kind,
+        }
+    }
+
+    fn error(&self, kind: JsonErrorKind) -> ParseJsonError {
+        self.error_at(self.position, kind)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.position += 1;
+        }
+    }
+
+    // Skips whitespace and then consumes `byte`, which describes itself as
+    // `what` in errors.
+    fn expect(&mut self, byte: u8, what: &'static str) -> ParseResult<()> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(found) if found == byte => {
+                self.position += 1;
+                Ok(())
+            },
+            Some(_) => Err(self.error(JsonErrorKind::Expected(what))),
+            None => Err(self.error(JsonErrorKind::UnexpectedEnd)),
+        }
+    }
+
+    fn end(&mut self) -> ParseResult<()> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(_) => Err(self.error(JsonErrorKind::TrailingCharacters)),
+            None => Ok(()),
+        }
+    }
+
+    // Parses the elements of an array, calling `element` with the parser
+    // positioned at each one.
+    fn array(&mut self,
+            mut element: impl FnMut(&mut Self) -> ParseResult<()>)
+            -> ParseResult<()> {
+        self.expect(b'[', "'['")?;
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.position += 1;
+            return Ok(());
+        }
+
+        loop {
+            element(self)?;
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(());
+                },
+                Some(_) => return Err(self.error(
+                    JsonErrorKind::Expected("',' or ']'"))),
+                None => return Err(self.error(JsonErrorKind::UnexpectedEnd)),
+            }
+        }
+    }
+
+    // Parses the members of an object, calling `member` with each key (and
+    // where the key started) and the parser positioned at its value.
+    fn object(&mut self,
+            mut member: impl FnMut(&mut Self, String, usize)
+                -> ParseResult<()>)
+            -> ParseResult<()> {
+        self.expect(b'{', "'{'")?;
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.position += 1;
+            return Ok(());
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key_position = self.position;
+            let key = self.string()?;
+            self.expect(b':', "':'")?;
+            member(self, key, key_position)?;
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b'}') => {
+                    self.position += 1;
+                    return Ok(());
+                },
+                Some(_) => return Err(self.error(
+                    JsonErrorKind::Expected("',' or '}'"))),
+                None => return Err(self.error(JsonErrorKind::UnexpectedEnd)),
+            }
+        }
+    }
+
+    fn spet<S: CreatableSpan>(&mut self) -> ParseResult<VecSpet<S>>
+            where S::Domain: JsonDomain {
+        let mut spans: Vec<S> = Vec::new();
+        self.array(|parser| {
+            spans.push(parser.span()?);
+            Ok(())
+        })?;
+
+        spans.sort();
+        Ok(VecSpet::from_sorted_iter(spans))
+    }
+
+    fn span<S: CreatableSpan>(&mut self) -> ParseResult<S>
+            where S::Domain: JsonDomain {
+        self.skip_whitespace();
+        let span_position = self.position;
+
+        let mut start: Option<S::Domain> = None;
+        let mut end: Option<S::Domain> = None;
+        match self.peek() {
+            Some(b'[') => {
+                self.position += 1;
+                start = Some(self.bound()?);
+                self.expect(b',', "','")?;
+                end = Some(self.bound()?);
+                self.expect(b']', "']'")?;
+            },
+            Some(b'{') => {
+                self.object(|parser, key, key_position| {
+                    let slot = match key.as_str() {
+                        "start" => &mut start,
+                        "end" => &mut end,
+                        _ => return parser.skip_value(0),
+                    };
+                    if slot.is_some() {
+                        return Err(parser.error_at(
+                            key_position, JsonErrorKind::DuplicateKey));
+                    }
+                    *slot = Some(parser.bound()?);
+                    Ok(())
+                })?;
+            },
+            Some(_) => return Err(self.error(
+                JsonErrorKind::Expected("'[' or '{'"))),
+            None => return Err(self.error(JsonErrorKind::UnexpectedEnd)),
+        }
+
+        let missing = |name| self.error_at(
+            span_position, JsonErrorKind::MissingBound(name));
+        let start = start.ok_or_else(|| missing("start"))?;
+        let end = end.ok_or_else(|| missing("end"))?;
+        if start > end {
+            return Err(self.error_at(span_position, JsonErrorKind::Reversed));
+        }
+        Ok(S::new(start, end))
+    }
+
+    fn bound<T: JsonDomain>(&mut self) -> ParseResult<T> {
+        self.skip_whitespace();
+        let position = self.position;
+        let scalar = match self.peek() {
+            Some(b'"') => JsonScalar::String(self.string()?),
+            Some(b'-' | b'0'..=b'9') => JsonScalar::Number(self.number()?),
+            Some(_) => return Err(self.error(
+                JsonErrorKind::Expected("a number or string"))),
+            None => return Err(self.error(JsonErrorKind::UnexpectedEnd)),
+        };
+
+        T::from_json(&scalar)
+            .ok_or_else(|| self.error_at(position, JsonErrorKind::InvalidBound))
+    }
+
+    fn number(&mut self) -> ParseResult<String> {
+        let start = self.position;
+        let invalid = |parser: &Self| parser.error_at(
+            start, JsonErrorKind::InvalidNumber);
+
+        if self.peek() == Some(b'-') {
+            self.position += 1;
+        }
+        match self.peek() {
+            Some(b'0') => self.position += 1,
+            Some(b'1'..=b'9') => self.digits(),
+            _ => return Err(invalid(self)),
+        }
+        if self.peek() == Some(b'.') {
+            self.position += 1;
+            if !self.peek().is_some_and(|b| b.is_ascii_digit()) {
+                return Err(invalid(self));
+            }
+            self.digits();
+        }
+        if let Some(b'e' | b'E') = self.peek() {
+            self.position += 1;
+            if let Some(b'+' | b'-') = self.peek() {
+                self.position += 1;
+            }
+            if !self.peek().is_some_and(|b| b.is_ascii_digit()) {
+                return Err(invalid(self));
+            }
+            self.digits();
+        }
+
+        Ok(self.text[start..self.position].to_string())
+    }
+
+    fn digits(&mut self) {
+        while self.peek().is_some_and(|b| b.is_ascii_digit()) {
+            self.position += 1;
+        }
+    }
+
+    fn string(&mut self) -> ParseResult<String> {
+        self.expect(b'"', "'\"'")?;
+
+        let mut result = String::new();
+        loop {
+            let rest = &self.text[self.position..];
+            let c = match rest.chars().next() {
+                Some(c) => c,
+                None => return Err(self.error(JsonErrorKind::UnexpectedEnd)),
+            };
+            match c {
+                '"' => {
+                    self.position += 1;
+                    return Ok(result);
+                },
+                '\\' => {
+                    let escape_position = self.position;
+                    self.position += 1;
+                    result.push(self.escape().ok_or_else(|| self.error_at(
+                        escape_position, JsonErrorKind::InvalidString))?);
+                },
+                c if (c as u32) < 0x20 => {
+                    return Err(self.error(JsonErrorKind::InvalidString));
+                },
+                c => {
+                    self.position += c.len_utf8();
+                    result.push(c);
+                },
+            }
+        }
+    }
+
+    // Decodes the escape after a backslash.
+    fn escape(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+        match c {
+            b'"' => Some('"'),
+            b'\\' => Some('\\'),
+            b'/' => Some('/'),
+            b'b' => Some('\u{8}'),
+            b'f' => Some('\u{c}'),
+            b'n' => Some('\n'),
+            b'r' => Some('\r'),
+            b't' => Some('\t'),
+            b'u' => {
+                let first = self.hex4()?;
+                if (0xd800..0xdc00).contains(&first) {
+                    // A high surrogate, which must be followed by a low one.
+                    if !self.text[self.position..].starts_with("\\u") {
+                        return None;
+                    }
+                    self.position += 2;
+                    let second = self.hex4()?;
+                    if !(0xdc00..0xe000).contains(&second) {
+                        return None;
+                    }
+                    char::from_u32(
+                        0x10000 + ((first - 0xd800) << 10) + (second - 0xdc00))
+                } else {
+                    char::from_u32(first)
+                }
+            },
+            _ => None,
+        }
+    }
+
+    fn hex4(&mut self) -> Option<u32> {
+        let digits = self.text.get(self.position..self.position + 4)?;
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        self.position += 4;
+        u32::from_str_radix(digits, 16).ok()
+    }
+
+    // Skips over any JSON value (ex: an unknown member of a span object).
+    // `depth` is how many arrays and objects it's inside of that were
+    // already being skipped.
+    fn skip_value(&mut self, depth: usize) -> ParseResult<()> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'"') => self.string().map(|_| ()),
+            Some(b'-' | b'0'..=b'9') => self.number().map(|_| ()),
+            Some(b'[' | b'{') if depth == MAX_SKIPPED_DEPTH => {
+                Err(self.error(JsonErrorKind::TooDeep))
+            },
+            Some(b'[') => self.array(|parser| parser.skip_value(depth + 1)),
+            Some(b'{') => {
+                self.object(|parser, _, _| parser.skip_value(depth + 1))
+            },
+            Some(_) => {
+                for literal in ["true", "false", "null"] {
+                    if self.text[self.position..].starts_with(literal) {
+                        self.position += literal.len();
+                        return Ok(());
+                    }
+                }
+                Err(self.error(JsonErrorKind::Expected("a value")))
+            },
+            None => Err(self.error(JsonErrorKind::UnexpectedEnd)),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::json::{
+        format_spet, format_keyed, parse_spet, parse_keyed, JsonStyle};
+    use crate::span::{Span, SimpleSpan, CreatableSpan};
+    use crate::vecspet::VecSpet;
+    use crate::float::OrderedF64;
+
+    type SSpan = SimpleSpan<i64>;
+    type Spet = VecSpet<SSpan>;
+
+    fn spet(spans: &[(i64, i64)]) -> Spet {
+        Spet::from_sorted_iter(
+            spans.iter().map(|(start, end)| SSpan::new(*start, *end)))
+    }
+
+    // SimpleSpan needs a Copy domain, so spans over strings need their own
+    // type.
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+    struct StringSpan {
+        start: String,
+        end: String,
+    }
+
+    impl Span for StringSpan {
+        type Domain = String;
+
+        fn start(&self) -> &String {
+            &self.start
+        }
+
+        fn end(&self) -> &String {
+            &self.end
+        }
+    }
+
+    impl CreatableSpan for StringSpan {
+        fn new(start: String, end: String) -> Self {
+            StringSpan { start, end }
+        }
+    }
+
+    mod format {
+        use crate::json::{format_spet, JsonStyle};
+        use crate::span::{SimpleSpan, CreatableSpan};
+        use crate::vecspet::VecSpet;
+        use crate::float::OrderedF64;
+        use super::{spet, StringSpan};
+
+        #[test]
+        fn pairs() {
+            assert_eq!(format_spet(&spet(&[(-7, -2), (1, 5)]),
+                                   JsonStyle::Pairs),
+                       "[[-7,-2],[1,5]]");
+        }
+
+        #[test]
+        fn objects() {
+            assert_eq!(format_spet(&spet(&[(1, 5)]), JsonStyle::Objects),
+                       r#"[{"start":1,"end":5}]"#);
+        }
+
+        #[test]
+        fn empty() {
+            assert_eq!(format_spet(&spet(&[]), JsonStyle::Pairs), "[]");
+        }
+
+        #[test]
+        fn floats() {
+            let a = VecSpet::<SimpleSpan<OrderedF64>>::from_sorted_iter(vec![
+                SimpleSpan::from_f64(0.5, 2.0),
+                SimpleSpan::from_f64(3.25, f64::INFINITY),
+            ]);
+
+            assert_eq!(format_spet(&a, JsonStyle::Pairs),
+                       "[[0.5,2],[3.25,1e999]]");
+        }
+
+        #[test]
+        fn strings_are_escaped() {
+            let a = VecSpet::<StringSpan>::from_sorted_iter(vec![
+                StringSpan::new("a\"b".to_string(),
+                                "c\\d\n\u{1}".to_string()),
+            ]);
+
+            assert_eq!(format_spet(&a, JsonStyle::Pairs),
+                       r#"[["a\"b","c\\d\n\u0001"]]"#);
+        }
+    }
+
+    mod parse {
+        use crate::json::parse_spet;
+        use crate::span::{SimpleSpan, CreatableSpan};
+        use crate::vecspet::VecSpet;
+        use crate::float::OrderedF64;
+        use super::{spet, Spet, StringSpan};
+
+        #[test]
+        fn pairs() {
+            assert_eq!(parse_spet::<SimpleSpan<i64>>("[[1, 5], [7, 9]]"),
+                       Ok(spet(&[(1, 5), (7, 9)])));
+        }
+
+        #[test]
+        fn objects_and_mixed() {
+            let text = r#"
+                [
+                    {"end": 9, "label": ["x", {"y": null}], "start": 7},
+                    [1, 5],
+                    {"start": 3, "end": 6}
+                ]
+            "#;
+
+            assert_eq!(parse_spet(text), Ok(spet(&[(1, 6), (7, 9)])));
+        }
+
+        #[test]
+        fn empty() {
+            assert_eq!(parse_spet(" [ ] "), Ok(Spet::default()));
+        }
+
+        #[test]
+        fn floats() {
+            let a = parse_spet::<SimpleSpan<OrderedF64>>(
+                "[[-1.5e1, 0], [0.25, 1e999]]").unwrap();
+
+            assert_eq!(a, VecSpet::from_sorted_iter(vec![
+                SimpleSpan::from_f64(-15.0, 0.0),
+                SimpleSpan::from_f64(0.25, f64::INFINITY),
+            ]));
+        }
+
+        #[test]
+        fn strings() {
+            let a = parse_spet::<StringSpan>(
+                r#"[["d", "e"], ["aé", "c😀"]]"#).unwrap();
+
+            assert_eq!(a, VecSpet::from_sorted_iter(vec![
+                StringSpan::new("a\u{e9}".to_string(),
+                                "c\u{1f600}".to_string()),
+                StringSpan::new("d".to_string(), "e".to_string()),
+            ]));
+        }
+    }
+
+    mod errors {
+        use crate::json::{parse_spet, parse_keyed, JsonErrorKind,
+                          ParseJsonError, MAX_SKIPPED_DEPTH};
+        use crate::span::SimpleSpan;
+        use super::spet;
+
+        fn check(text: &str) -> (usize, usize, JsonErrorKind) {
+            let err = parse_spet::<SimpleSpan<i64>>(text).unwrap_err();
+            (err.line, err.column, err.kind)
+        }
+
+        #[test]
+        fn positions() {
+            assert_eq!(check("[[1, 5],\n [7 9]]"),
+                       (2, 5, JsonErrorKind::Expected("','")));
+            assert_eq!(check("[[\"é\", 5]]"),
+                       (1, 3, JsonErrorKind::InvalidBound));
+        }
+
+        #[test]
+        fn unexpected_end() {
+            assert_eq!(check(""), (1, 1, JsonErrorKind::UnexpectedEnd));
+            assert_eq!(check("[[1, 5]"), (1, 8, JsonErrorKind::UnexpectedEnd));
+            assert_eq!(check("[[\"1"), (1, 5, JsonErrorKind::UnexpectedEnd));
+        }
+
+        #[test]
+        fn invalid_number() {
+            assert_eq!(check("[[01, 5]]"),
+                       (1, 4, JsonErrorKind::Expected("','")));
+            assert_eq!(check("[[1., 5]]"),
+                       (1, 3, JsonErrorKind::InvalidNumber));
+            assert_eq!(check("[[-, 5]]"),
+                       (1, 3, JsonErrorKind::InvalidNumber));
+        }
+
+        #[test]
+        fn integer_bounds_reject_fractions() {
+            assert_eq!(check("[[1.5, 5]]"),
+                       (1, 3, JsonErrorKind::InvalidBound));
+        }
+
+        #[test]
+        fn invalid_string() {
+            let err = parse_spet::<super::StringSpan>(r#"[["a\x", "b"]]"#)
+                .unwrap_err();
+            assert_eq!((err.column, err.kind),
+                       (5, JsonErrorKind::InvalidString));
+
+            let err = parse_spet::<super::StringSpan>(r#"[["\ud83d", "b"]]"#)
+                .unwrap_err();
+            assert_eq!(err.kind, JsonErrorKind::InvalidString);
+        }
+
+        #[test]
+        fn missing_bound() {
+            assert_eq!(check("[{\"start\": 1}]"),
+                       (1, 2, JsonErrorKind::MissingBound("end")));
+        }
+
+        #[test]
+        fn duplicate_bound() {
+            assert_eq!(check("[{\"start\": 1, \"start\": 2, \"end\": 3}]"),
+                       (1, 15, JsonErrorKind::DuplicateKey));
+        }
+
+        #[test]
+        fn reversed() {
+            assert_eq!(check("[[1, 5], [9, 7]]"),
+                       (1, 10, JsonErrorKind::Reversed));
+        }
+
+        #[test]
+        fn deeply_nested_unknown_member() {
+            let text = format!("[{{\"start\": 1, \"end\": 2, \"x\": {}",
+                               "[".repeat(200_000));
+            assert_eq!(check(&text),
+                       (1, 30 + MAX_SKIPPED_DEPTH, JsonErrorKind::TooDeep));
+
+            let nested = format!("{}{}", "[".repeat(MAX_SKIPPED_DEPTH),
+                                 "]".repeat(MAX_SKIPPED_DEPTH));
+            let text = format!("[{{\"start\": 1, \"end\": 2, \"x\": {}}}]",
+                               nested);
+            assert_eq!(parse_spet(&text), Ok(spet(&[(1, 2)])));
+        }
+
+        #[test]
+        fn trailing_characters() {
+            assert_eq!(check("[] []"),
+                       (1, 4, JsonErrorKind::TrailingCharacters));
+        }
+
+        #[test]
+        fn duplicate_key() {
+            let err = parse_keyed::<SimpleSpan<i64>>(
+                "{\"a\": [], \"a\": []}").unwrap_err();
+            assert_eq!(err, ParseJsonError {
+                line: 1,
+                column: 11,
+                kind: JsonErrorKind::DuplicateKey,
+            });
+        }
+
+        #[test]
+        fn display() {
+            assert_eq!(parse_spet::<SimpleSpan<i64>>("[[1, 5]")
+                           .unwrap_err().to_string(),
+                       "unexpected end of input at line 1, column 8");
+        }
+    }
+
+    #[test]
+    fn keyed_round_trip() {
+        let mut spets: HashMap<String, Spet> = HashMap::new();
+        spets.insert("bob".to_string(), spet(&[(3, 8)]));
+        spets.insert("alice".to_string(), spet(&[(1, 2), (4, 5)]));
+
+        let text = format_keyed(&spets, JsonStyle::Objects);
+        assert_eq!(text,
+                   r#"{"alice":[{"start":1,"end":2},{"start":4,"end":5}],"#
+                       .to_string()
+                   + r#""bob":[{"start":3,"end":8}]}"#);
+        assert_eq!(parse_keyed(&text), Ok(spets));
+    }
+
+    #[test]
+    fn round_trips() {
+        let a = spet(&[(i64::MIN, -3), (0, 1), (5, i64::MAX)]);
+        for style in [JsonStyle::Pairs, JsonStyle::Objects] {
+            assert_eq!(parse_spet(&format_spet(&a, style)), Ok(a.clone()));
+        }
+
+        let b = VecSpet::<SimpleSpan<OrderedF64>>::from_sorted_iter(vec![
+            SimpleSpan::from_f64(f64::NEG_INFINITY, 0.1),
+            SimpleSpan::from_f64(1e-7, 3e300),
+        ]);
+        assert_eq!(parse_spet(&format_spet(&b, JsonStyle::Pairs)), Ok(b));
+    }
+}
@@ -3,3 +3,25 @@ pub mod span;
 pub mod points;
 pub mod vecspet;
 pub mod overlapping;
+pub mod measure;
+pub mod sliding;
+pub mod similarity;
+pub mod notation;
+pub mod float;
+pub mod time;
+pub mod iso8601;
+pub mod csv;
+pub mod binary;
+pub mod view;
+pub mod json;
+pub mod icalendar;
+pub mod recurrence;
+pub mod circular;
+pub mod sessionize;
+pub mod spetmap;
+pub mod slots;
+
+pub use span::{Span, SimpleSpan, CreatableSpan};
+pub use vecspet::VecSpet;
+pub use spetmap::SpetMap;
+pub use float::OrderedF64;
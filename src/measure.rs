@@ -0,0 +1,255 @@
+use std::convert::TryFrom;
+use std::ops::{Add, Sub};
+
+
+/**
+ * A domain whose values can be measured against and offset by one another.
+ *
+ * Ex: for timestamps the measure is a duration, since subtracting one
+ * timestamp from another gives a duration and adding a duration to a timestamp
+ * gives another timestamp.
+ *
+ * Operations that grow, shrink, or move spans (ex: VecSpet::dilate) need their
+ * span's domain to implement this.
+ */
+pub trait Measurable: Ord + Clone {
+    type Measure: Ord + Clone + Default
+        + Add<Output = Self::Measure>
+        + Sub<Output = Self::Measure>;
+
+    /**
+     * The measure between self and `later`, where `later >= self`.
+     */
+    fn measure_to(&self, later: &Self) -> Self::Measure;
+
+    fn offset_forward(&self, by: &Self::Measure) -> Self;
+    fn offset_backward(&self, by: &Self::Measure) -> Self;
+
+    /**
+     * Like offset_forward(), but gives None rather than saturating at the
+     * edge of the domain.
+     *
+     * The default implementation checks that offsetting back again gives
+     * self, which catches saturation but costs a second offset.
+     */
+    fn checked_offset_forward(&self, by: &Self::Measure) -> Option<Self> {
+        let offset = self.offset_forward(by);
+        if offset.offset_backward(by) == *self {
+            Some(offset)
+        } else {
+            None
+        }
+    }
+
+    /**
+     * Like offset_backward(), but gives None rather than saturating at the
+     * edge of the domain.
+     */
+    fn checked_offset_backward(&self, by: &Self::Measure) -> Option<Self> {
+        let offset = self.offset_backward(by);
+        if offset.offset_forward(by) == *self {
+            Some(offset)
+        } else {
+            None
+        }
+    }
+
+    /**
+     * Approximates a measure as a float, so that measures can be compared as
+     * ratios (ex: by similarity::jaccard).
+     */
+    fn measure_as_f64(measure: &Self::Measure) -> f64;
+
+    /**
+     * Finds the greatest `origin + k * width` (for some whole number `k`)
+     * that's no greater than self, where `self >= origin`.
+     *
+     * The default implementation steps forward from `origin` one `width` at a
+     * time, so domains that can divide should override it.
+     */
+    fn floor_to_grid(&self, origin: &Self, width: &Self::Measure) -> Self {
+        assert!(*width > Self::Measure::default());
+        let mut floor = origin.clone();
+        loop {
+            let next = floor.offset_forward(width);
+            if next > *self || next <= floor {
+                return floor;
+            }
+            floor = next;
+        }
+    }
+
+    /**
+     * Finds `self + n * width`, the `n`th point of a grid starting at self,
+     * or None if it's past what the domain can represent.
+     *
+     * This is worked out in one go where possible, since adding `width` over
+     * and over can drift for floats. The default implementation steps
+     * forward one `width` at a time (giving None if a step doesn't move
+     * forward), so domains that can multiply should override it.
+     */
+    fn nth_grid_point(&self, width: &Self::Measure, n: u64) -> Option<Self> {
+        if *width == Self::Measure::default() {
+            return Some(self.clone());
+        }
+
+        let mut point = self.clone();
+        for _ in 0..n {
+            let next = point.offset_forward(width);
+            if next <= point {
+                return None;
+            }
+            point = next;
+        }
+        Some(point)
+    }
+}
+
+
+// Offsets saturate at the bounds of the integer type, so padding a span that
+// starts at 0 in an unsigned domain clamps it to 0 rather than panicking.
+macro_rules! impl_measurable_for_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Measurable for $t {
+                type Measure = $t;
+
+                fn measure_to(&self, later: &Self) -> Self::Measure {
+                    later.saturating_sub(*self)
+                }
+
+                fn offset_forward(&self, by: &Self::Measure) -> Self {
+                    self.saturating_add(*by)
+                }
+
+                fn offset_backward(&self, by: &Self::Measure) -> Self {
+                    self.saturating_sub(*by)
+                }
+
+                fn checked_offset_forward(&self, by: &Self::Measure)
+                        -> Option<Self> {
+                    self.checked_add(*by)
+                }
+
+                fn checked_offset_backward(&self, by: &Self::Measure)
+                        -> Option<Self> {
+                    self.checked_sub(*by)
+                }
+
+                fn measure_as_f64(measure: &Self::Measure) -> f64 {
+                    *measure as f64
+                }
+
+                fn floor_to_grid(&self, origin: &Self, width: &Self::Measure)
+                        -> Self {
+                    let steps = origin.measure_to(self) / *width;
+                    origin.offset_forward(&(steps * *width))
+                }
+
+                fn nth_grid_point(&self, width: &Self::Measure, n: u64)
+                        -> Option<Self> {
+                    match <$t>::try_from(n).ok()
+                            .and_then(|n| width.checked_mul(n)) {
+                        Some(offset) => self.checked_add(offset),
+                        None if *width == 0 => Some(*self),
+
+                        // The offset is too big for the type, but the point
+                        // might not be (ex: if self is negative), so it's
+                        // found in two halves.
+                        None => {
+                            let half = n / 2;
+                            self.nth_grid_point(width, half)?
+                                .nth_grid_point(width, n - half)
+                        },
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_measurable_for_integer!(
+    i8, i16, i32, i64, i128, isize,
+    u8, u16, u32, u64, u128, usize);
+
+
+#[cfg(test)]
+mod tests {
+    use crate::measure::Measurable;
+
+    #[test]
+    fn integer_measure() {
+        assert_eq!(3i32.measure_to(&7), 4);
+        assert_eq!((-3i32).measure_to(&7), 10);
+    }
+
+    #[test]
+    fn integer_offsets() {
+        assert_eq!(3i32.offset_forward(&4), 7);
+        assert_eq!(3i32.offset_backward(&4), -1);
+    }
+
+    #[test]
+    fn integer_floor_to_grid() {
+        assert_eq!(17i32.floor_to_grid(&2, &5), 17);
+        assert_eq!(16i32.floor_to_grid(&2, &5), 12);
+        assert_eq!(2i32.floor_to_grid(&2, &5), 2);
+        assert_eq!(1i32.floor_to_grid(&-3, &4), 1);
+    }
+
+    #[test]
+    fn default_floor_to_grid() {
+        // Doesn't override floor_to_grid, so it gets the stepping version.
+        #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+        struct Tick(i32);
+
+        impl Measurable for Tick {
+            type Measure = i32;
+
+            fn measure_to(&self, later: &Self) -> i32 {
+                later.0 - self.0
+            }
+
+            fn offset_forward(&self, by: &i32) -> Self {
+                Tick(self.0 + by)
+            }
+
+            fn offset_backward(&self, by: &i32) -> Self {
+                Tick(self.0 - by)
+            }
+
+            fn measure_as_f64(measure: &i32) -> f64 {
+                *measure as f64
+            }
+        }
+
+        assert_eq!(Tick(17).floor_to_grid(&Tick(2), &5), Tick(17));
+        assert_eq!(Tick(16).floor_to_grid(&Tick(2), &5), Tick(12));
+        assert_eq!(Tick(2).floor_to_grid(&Tick(2), &5), Tick(2));
+        assert_eq!(Tick(2).nth_grid_point(&5, 3), Some(Tick(17)));
+        assert_eq!(Tick(2).checked_offset_backward(&5), Some(Tick(-3)));
+    }
+
+    #[test]
+    fn integer_nth_grid_point() {
+        assert_eq!(3i32.nth_grid_point(&4, 5), Some(23));
+        assert_eq!((-100i8).nth_grid_point(&1, 200), Some(100));
+        assert_eq!(250u8.nth_grid_point(&4, 2), None);
+        assert_eq!(0u8.nth_grid_point(&1, u64::MAX), None);
+        assert_eq!(7u8.nth_grid_point(&0, u64::MAX), Some(7));
+    }
+
+    #[test]
+    fn unsigned_offsets_saturate() {
+        assert_eq!(3u32.offset_backward(&4), 0);
+        assert_eq!(u32::MAX.offset_forward(&1), u32::MAX);
+    }
+
+    #[test]
+    fn checked_offsets() {
+        assert_eq!(3u32.checked_offset_backward(&4), None);
+        assert_eq!(u32::MAX.checked_offset_forward(&1), None);
+        assert_eq!(i64::MIN.checked_offset_forward(&-1), None);
+        assert_eq!(3i64.checked_offset_backward(&-4), Some(7));
+    }
+}
@@ -0,0 +1,367 @@
+use std::fmt::{self, Display, Write};
+use std::str::FromStr;
+use crate::span::{Span, SimpleSpan, CreatableSpan};
+use crate::vecspet::VecSpet;
+
+
+/**
+ * How the bounds of each span are bracketed.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Brackets {
+    // `1.2–4`
+    Bare,
+
+    // `[1.2–4)`, making it explicit that the start is included and the end
+    // isn't.
+    HalfOpen,
+}
+
+
+/**
+ * The notation used in the README, ex: `{1.2–4, 8.1–8.4}`.
+ *
+ * The Display and FromStr implementations of SimpleSpan and VecSpet use the
+ * default notation (bare bounds). When parsing, they accept either bare or
+ * half-open bounds. Both an en dash (`–`) and a hyphen (`-`) are accepted
+ * between the start and end of a span.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Notation {
+    pub brackets: Brackets,
+}
+
+
+impl Default for Notation {
+    fn default() -> Self {
+        Notation { brackets: Brackets::Bare }
+    }
+}
+
+
+impl Notation {
+    pub fn format_span<S: Span>(&self, span: &S) -> String
+            where S::Domain: Display {
+        let mut result = String::new();
+        self.write_span(&mut result, span).unwrap();
+        result
+    }
+
+    pub fn format_spet<S: CreatableSpan>(&self, spet: &VecSpet<S>) -> String
+            where S::Domain: Display {
+        let mut result = String::new();
+        self.write_spet(&mut result, spet).unwrap();
+        result
+    }
+
+    /**
+     * Parses a span, requiring its bounds to be bracketed in this notation's
+     * style.
+     */
+    pub fn parse_span<S: CreatableSpan>(&self, text: &str)
+            -> Result<S, ParseNotationError>
+            where S::Domain: FromStr {
+        parse_span(text, Some(self.brackets))
+    }
+
+    /**
+     * Parses a spet, requiring its spans' bounds to be bracketed in this
+     * notation's style. The spans may be in any order and may overlap.
+     */
+    pub fn parse_spet<S: CreatableSpan>(&self, text: &str)
+            -> Result<VecSpet<S>, ParseNotationError>
+            where S::Domain: FromStr {
+        parse_spet(text, Some(self.brackets))
+    }
+
+    fn write_span<S: Span>(&self, out: &mut impl Write, span: &S)
+            -> fmt::Result
+            where S::Domain: Display {
+        match self.brackets {
+            Brackets::Bare => write!(out, "{}–{}", span.start(), span.end()),
+            Brackets::HalfOpen =>
+                write!(out, "[{}–{})", span.start(), span.end()),
+        }
+    }
+
+    fn write_spet<S: CreatableSpan>(&self,
+            out: &mut impl Write,
+            spet: &VecSpet<S>) -> fmt::Result
+            where S::Domain: Display {
+        out.write_char('{')?;
+        for (i, span) in spet.into_iter().enumerate() {
+            if i > 0 {
+                out.write_str(", ")?;
+            }
+            self.write_span(out, span)?;
+        }
+        out.write_char('}')
+    }
+}
+
+
+/**
+ * Error from parsing a span or spet written in Notation.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseNotationError {
+    // The spet wasn't wrapped in `{` and `}`.
+    MissingBraces,
+
+    // The span (given) had no `–` or `-` between its start and end.
+    MissingSeparator(String),
+
+    // The start or end of the span (given) couldn't be parsed.
+    InvalidBound(String),
+
+    // The span (given) wasn't bracketed the way the notation requires.
+    WrongBrackets(String),
+
+    // The span (given) ends before it starts.
+    Reversed(String),
+}
+
+
+impl Display for ParseNotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use ParseNotationError::*;
+        match self {
+            MissingBraces => write!(f, "spet must be wrapped in {{ and }}"),
+            MissingSeparator(span) =>
+                write!(f, "no – or - between start and end of {:?}", span),
+            InvalidBound(span) => write!(f, "invalid bound in {:?}", span),
+            WrongBrackets(span) =>
+                write!(f, "unexpected bracket style in {:?}", span),
+            Reversed(span) => write!(f, "{:?} ends before it starts", span),
+        }
+    }
+}
+
+
+impl std::error::Error for ParseNotationError {}
+
+
+// Parses a single span. If `brackets` is None, either bracket style is
+// accepted.
+fn parse_span<S: CreatableSpan>(text: &str, brackets: Option<Brackets>)
+        -> Result<S, ParseNotationError>
+        where S::Domain: FromStr {
+    let text = text.trim();
+    let (inner, found) =
+        match text.strip_prefix('[').and_then(|t| t.strip_suffix(')')) {
+            Some(inner) => (inner, Brackets::HalfOpen),
+            None => (text, Brackets::Bare),
+        };
+    if brackets.is_some_and(|brackets| brackets != found) {
+        return Err(ParseNotationError::WrongBrackets(text.to_string()));
+    }
+
+    // A hyphen could also be a minus sign (ex: `-5--3`), so try splitting at
+    // each candidate until both halves parse.
+    let mut found_separator = false;
+    for (i, c) in inner.char_indices().filter(|(i, _)| *i > 0) {
+        if c != '–' && c != '-' {
+            continue;
+        }
+        found_separator = true;
+
+        let start = inner[..i].trim().parse::<S::Domain>();
+        let end = inner[i + c.len_utf8()..].trim().parse::<S::Domain>();
+        if let (Ok(start), Ok(end)) = (start, end) {
+            if start > end {
+                return Err(ParseNotationError::Reversed(text.to_string()));
+            }
+            return Ok(S::new(start, end));
+        }
+    }
+
+    if found_separator {
+        Err(ParseNotationError::InvalidBound(text.to_string()))
+    } else {
+        Err(ParseNotationError::MissingSeparator(text.to_string()))
+    }
+}
+
+
+fn parse_spet<S: CreatableSpan>(text: &str, brackets: Option<Brackets>)
+        -> Result<VecSpet<S>, ParseNotationError>
+        where S::Domain: FromStr {
+    let inner = text.trim()
+        .strip_prefix('{')
+        .and_then(|text| text.strip_suffix('}'))
+        .ok_or(ParseNotationError::MissingBraces)?
+        .trim();
+
+    let mut spans: Vec<S> = Vec::new();
+    if !inner.is_empty() {
+        for span in inner.split(',') {
+            spans.push(parse_span(span, brackets)?);
+        }
+    }
+
+    spans.sort();
+    Ok(VecSpet::from_sorted_iter(spans))
+}
+
+
+impl<T: Ord + Copy + Display> Display for SimpleSpan<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Notation::default().write_span(f, self)
+    }
+}
+
+
+impl<T: Ord + Copy + FromStr> FromStr for SimpleSpan<T> {
+    type Err = ParseNotationError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        parse_span(text, None)
+    }
+}
+
+
+impl<S: CreatableSpan> Display for VecSpet<S> where S::Domain: Display {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Notation::default().write_spet(f, self)
+    }
+}
+
+
+impl<S: CreatableSpan> FromStr for VecSpet<S> where S::Domain: FromStr {
+    type Err = ParseNotationError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        parse_spet(text, None)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::notation::{Notation, Brackets, ParseNotationError};
+    use crate::span::{SimpleSpan, CreatableSpan};
+    use crate::vecspet::VecSpet;
+
+    type SSpan = SimpleSpan<i32>;
+    type Spet = VecSpet<SSpan>;
+
+    mod display {
+        use super::*;
+
+        #[test]
+        fn span() {
+            assert_eq!(SSpan::new(1, 4).to_string(), "1–4");
+        }
+
+        #[test]
+        fn spet() {
+            let spet = Spet::from_sorted_iter(vec![
+                SSpan::new(1, 4),
+                SSpan::new(8, 9),
+            ]);
+
+            assert_eq!(spet.to_string(), "{1–4, 8–9}");
+        }
+
+        #[test]
+        fn empty_spet() {
+            assert_eq!(Spet::default().to_string(), "{}");
+        }
+
+        #[test]
+        fn half_open() {
+            let notation = Notation { brackets: Brackets::HalfOpen };
+            let spet = Spet::from_sorted_iter(vec![
+                SSpan::new(1, 4),
+                SSpan::new(8, 9),
+            ]);
+
+            assert_eq!(notation.format_span(&SSpan::new(1, 4)), "[1–4)");
+            assert_eq!(notation.format_spet(&spet), "{[1–4), [8–9)}");
+        }
+    }
+
+    mod parse {
+        use super::*;
+
+        #[test]
+        fn span_with_en_dash() {
+            assert_eq!("1–4".parse(), Ok(SSpan::new(1, 4)));
+        }
+
+        #[test]
+        fn span_with_hyphen() {
+            assert_eq!(" 1 - 4 ".parse(), Ok(SSpan::new(1, 4)));
+        }
+
+        #[test]
+        fn negative_bounds() {
+            assert_eq!("-5--3".parse(), Ok(SSpan::new(-5, -3)));
+            assert_eq!("-5–3".parse(), Ok(SSpan::new(-5, 3)));
+        }
+
+        #[test]
+        fn half_open_span() {
+            assert_eq!("[1–4)".parse(), Ok(SSpan::new(1, 4)));
+        }
+
+        #[test]
+        fn spet() {
+            assert_eq!("{1–4, 8-9}".parse(), Ok(Spet::from_sorted_iter(vec![
+                SSpan::new(1, 4),
+                SSpan::new(8, 9),
+            ])));
+        }
+
+        #[test]
+        fn unsorted_overlapping_spet() {
+            assert_eq!("{8–9, 1–4, 3–5}".parse(),
+                       Ok(Spet::from_sorted_iter(vec![
+                           SSpan::new(1, 5),
+                           SSpan::new(8, 9),
+                       ])));
+        }
+
+        #[test]
+        fn empty_spet() {
+            assert_eq!("{ }".parse(), Ok(Spet::default()));
+        }
+
+        #[test]
+        fn round_trip() {
+            let spet = Spet::from_sorted_iter(vec![
+                SSpan::new(-4, -1),
+                SSpan::new(8, 9),
+            ]);
+
+            assert_eq!(spet.to_string().parse(), Ok(spet));
+        }
+
+        #[test]
+        fn strict_brackets() {
+            let notation = Notation { brackets: Brackets::HalfOpen };
+
+            assert_eq!(notation.parse_span("[1–4)"), Ok(SSpan::new(1, 4)));
+            assert_eq!(notation.parse_span::<SSpan>("1–4"),
+                       Err(ParseNotationError::WrongBrackets(
+                           "1–4".to_string())));
+            assert_eq!(Notation::default().parse_spet::<SSpan>("{[1–4)}"),
+                       Err(ParseNotationError::WrongBrackets(
+                           "[1–4)".to_string())));
+        }
+
+        #[test]
+        fn errors() {
+            assert_eq!("1–4".parse::<Spet>(),
+                       Err(ParseNotationError::MissingBraces));
+            assert_eq!("{1 4}".parse::<Spet>(),
+                       Err(ParseNotationError::MissingSeparator(
+                           "1 4".to_string())));
+            assert_eq!("{1–x}".parse::<Spet>(),
+                       Err(ParseNotationError::InvalidBound(
+                           "1–x".to_string())));
+            assert_eq!("4–1".parse::<SSpan>(),
+                       Err(ParseNotationError::Reversed("4–1".to_string())));
+        }
+    }
+}
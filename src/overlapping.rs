@@ -48,9 +48,108 @@ pub fn n_overlapping<T: CreatableSpan, I: IntoIterator>(
     VecSpet::from_sorted_iter(result_spans)
 }
 
+
+/**
+ * Finds the most spans that overlap at once (ex: the peak number of
+ * concurrent sessions), along with everywhere that many overlap.
+ *
+ * Takes spets the same way n_overlapping() does. Spans are half-open, so one
+ * that ends where another starts doesn't overlap it. Gives a depth of 0 (and
+ * no spans) if there are no non-empty spans.
+ */
+pub fn max_overlap<T: CreatableSpan, I: IntoIterator>(spets: I)
+        -> (usize, VecSpet<T>)
+        where I::Item: IntoIterator,
+              <I::Item as IntoIterator>::Item: Span,
+              <<I::Item as IntoIterator>::Item as Span>::Domain: Into<T::Domain> {
+    // Which spet a span came from doesn't matter, so they're all swept as
+    // one.
+    let all = sorted_chain(converted(spets));
+    let (depth, spans) = max_overlap_by(vec![all], |_| ());
+    (depth, VecSpet {
+        spans: spans.into_iter().map(|(span, _)| span).collect(),
+    })
+}
+
+
+/**
+ * Like max_overlap(), but says which spets overlap in each span as the
+ * (ascending) indices of the spets in `spets`.
+ *
+ * A new span starts whenever the overlapping spets change, so spans can
+ * touch (ex: if one session ends right as another starts while the peak
+ * holds).
+ */
+pub fn max_overlap_attributed<T: CreatableSpan, I: IntoIterator>(spets: I)
+        -> (usize, Vec<(T, Vec<usize>)>)
+        where I::Item: IntoIterator,
+              <I::Item as IntoIterator>::Item: Span,
+              <<I::Item as IntoIterator>::Item as Span>::Domain: Into<T::Domain> {
+    max_overlap_by(converted(spets), |counts| {
+        (0..counts.len()).filter(|i| counts[*i] > 0).collect()
+    })
+}
+
+
+// Gives an iterator over each spet's spans as T.
+fn converted<T: CreatableSpan, I: IntoIterator>(spets: I)
+        -> Vec<impl Iterator<Item = T>>
+        where I::Item: IntoIterator,
+              <I::Item as IntoIterator>::Item: Span,
+              <<I::Item as IntoIterator>::Item as Span>::Domain: Into<T::Domain> {
+    spets.into_iter()
+        .map(|spans| spans.into_iter().map(|span| {
+            T::new(span.start().clone().into(), span.end().clone().into())
+        }))
+        .collect()
+}
+
+
+// Does a single sweep for max_overlap() and max_overlap_attributed(). `key`
+// is given how many spans from each spet overlap at the current position, and
+// adjacent spans at the peak depth are merged when their keys are equal.
+fn max_overlap_by<T, I, K>(spets: Vec<I>, key: impl Fn(&[isize]) -> K)
+        -> (usize, Vec<(T, K)>)
+        where T: CreatableSpan,
+              I: Iterator<Item = T>,
+              K: PartialEq {
+    let mut max_depth = 0;
+    let mut result = Vec::new();
+    let mut pending: Option<(T::Domain, K)> = None;
+    VecSpet::<T>::sweep(spets, |position, counts| {
+        let depth: isize = counts.iter().sum();
+        if depth < max_depth {
+            if let Some((start, current)) = pending.take() {
+                result.push((T::new(start, position.clone()), current));
+            }
+            return;
+        } else if depth == 0 {
+            return;
+        } else if depth > max_depth {
+            max_depth = depth;
+            result.clear();
+            pending = None;
+        }
+
+        let here = key(counts);
+        pending = match pending.take() {
+            Some((start, current)) if current == here => Some((start, current)),
+            Some((start, current)) => {
+                result.push((T::new(start, position.clone()), current));
+                Some((position.clone(), here))
+            },
+            None => Some((position.clone(), here)),
+        };
+    });
+
+    // Counts are never negative once every point at a position is seen.
+    (max_depth as usize, result)
+}
+
 #[cfg(test)]
 mod test {
-    use crate::overlapping::n_overlapping;
+    use crate::overlapping::{
+        n_overlapping, max_overlap, max_overlap_attributed};
     use crate::span::{SimpleSpan, CreatableSpan};
     use crate::vecspet::VecSpet;
 
@@ -89,4 +188,112 @@ mod test {
         let src: Vec<SSpan> = Vec::new();
         assert_eq!(result, Spet::from_sorted_iter(src));
     }
+
+    mod max_overlap {
+        use super::*;
+
+        #[test]
+        fn peak_sessions() {
+            let (depth, spans): (usize, Spet) = max_overlap(vec![
+                vec![SSpan::new(1, 5)],
+                vec![SSpan::new(2, 4)],
+                vec![SSpan::new(3, 8)],
+                vec![SSpan::new(7, 9)],
+                vec![SSpan::new(7, 10)],
+            ]);
+
+            assert_eq!(depth, 3);
+            assert_eq!(spans, Spet::from_sorted_iter(vec![
+                SSpan::new(3, 4),
+                SSpan::new(7, 8),
+            ]));
+        }
+
+        #[test]
+        fn touching_spans_do_not_overlap() {
+            let (depth, spans): (usize, Spet) = max_overlap(vec![
+                vec![SSpan::new(1, 3), SSpan::new(5, 7)],
+                vec![SSpan::new(3, 5)],
+            ]);
+
+            assert_eq!(depth, 1);
+            assert_eq!(spans, Spet::from_sorted_iter(vec![SSpan::new(1, 7)]));
+        }
+
+        #[test]
+        fn empty_spans_are_ignored() {
+            let (depth, spans): (usize, Spet) = max_overlap(vec![
+                vec![SSpan::new(1, 4)],
+                vec![SSpan::new(2, 2)],
+            ]);
+
+            assert_eq!(depth, 1);
+            assert_eq!(spans, Spet::from_sorted_iter(vec![SSpan::new(1, 4)]));
+        }
+
+        #[test]
+        fn empty_span_outside_others() {
+            let (depth, spans): (usize, Spet) = max_overlap(vec![
+                vec![SSpan::new(3, 4)],
+                vec![SSpan::new(10, 10)],
+            ]);
+
+            assert_eq!(depth, 1);
+            assert_eq!(spans, Spet::from_sorted_iter(vec![SSpan::new(3, 4)]));
+        }
+
+        #[test]
+        fn no_spans() {
+            let (depth, spans): (usize, Spet) =
+                max_overlap(Vec::<Vec<SSpan>>::new());
+
+            assert_eq!(depth, 0);
+            assert_eq!(spans, Spet::default());
+        }
+    }
+
+    mod max_overlap_attributed {
+        use super::*;
+
+        #[test]
+        fn says_which_spets() {
+            let (depth, spans) = max_overlap_attributed(vec![
+                vec![SSpan::new(1, 5)],
+                vec![SSpan::new(2, 4)],
+                vec![SSpan::new(4, 6)],
+                vec![SSpan::new(8, 9)],
+            ]);
+
+            assert_eq!(depth, 2);
+            assert_eq!(spans, vec![
+                (SSpan::new(2, 4), vec![0, 1]),
+                (SSpan::new(4, 5), vec![0, 2]),
+            ]);
+        }
+
+        #[test]
+        fn overlap_within_a_spet() {
+            let (depth, spans) = max_overlap_attributed(vec![
+                vec![SSpan::new(1, 5), SSpan::new(2, 6)],
+                vec![SSpan::new(5, 9)],
+            ]);
+
+            assert_eq!(depth, 2);
+            assert_eq!(spans, vec![
+                (SSpan::new(2, 5), vec![0]),
+                (SSpan::new(5, 6), vec![0, 1]),
+            ]);
+        }
+
+        #[test]
+        fn empty_span_outside_others() {
+            let (depth, spans) = max_overlap_attributed(vec![
+                vec![SSpan::new(1, 2), SSpan::new(5, 5)],
+                vec![SSpan::new(1, 3)],
+            ]);
+
+            assert_eq!(depth, 2);
+            assert_eq!(spans, vec![(SSpan::new(1, 2), vec![0, 1])]);
+        }
+    }
 }
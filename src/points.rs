@@ -1,5 +1,7 @@
 use std::fmt::{Debug};
-use crate::span::Span;
+use std::iter::Peekable;
+use crate::span::{Span, Tagged};
+use crate::mergeiter::{sorted_chain, SortedChain};
 
 /**
  * A reference to the start or end of a span.
@@ -41,7 +43,7 @@ impl<S: Span> OrderableEndPoint<S> {
 
 impl<S: Span> Ord for OrderableEndPoint<S> {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.value().cmp(&other.value())
+        self.value().cmp(other.value())
     }
 }
 
@@ -162,7 +164,81 @@ pub fn enumerate_points<I: IntoIterator>(iterable: I) -> PointIterator<I::IntoIt
         ends: BinaryHeap::new(),
     }
 }
-    
+
+
+// Tags each span from `spans` with `tag`.
+struct TagWith<I: Iterator> where I::Item: Span {
+    spans: I,
+    tag: usize,
+}
+
+
+impl<I: Iterator> Iterator for TagWith<I> where I::Item: Span {
+    type Item = Tagged<I::Item, usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let span = self.spans.next()?;
+        Some(Tagged { span, tag: self.tag })
+    }
+}
+
+
+/**
+ * Sweeps across the points of several sorted iterators of spans at once
+ * (ex: several spets), keeping count of how many spans from each cover the
+ * current position.
+ *
+ * Counts are signed since the points at a single position can come in any
+ * order (ex: an empty span's end can come before its start), but they're
+ * never negative once every point at a position has been seen.
+ */
+pub(crate) struct Sweep<I: Iterator> where I::Item: Span {
+    points: Peekable<PointIterator<SortedChain<TagWith<I>>>>,
+    counts: Vec<isize>,
+}
+
+
+impl<I: Iterator> Sweep<I> where I::Item: Span {
+    pub fn new(spans: Vec<I>) -> Self {
+        let counts = vec![0; spans.len()];
+        let tagged = spans.into_iter().enumerate()
+            .map(|(tag, spans)| TagWith { spans, tag });
+        Sweep {
+            points: enumerate_points(sorted_chain(tagged)).peekable(),
+            counts,
+        }
+    }
+
+    /**
+     * Moves to the next position that has a point, giving that position
+     * along with how many spans from each iterator cover what comes right
+     * after it.
+     */
+    pub fn next_position(&mut self)
+            -> Option<(<I::Item as Span>::Domain, &[isize])> {
+        use Point::{StartOf, EndOf};
+        while let Some(point) = self.points.next() {
+            let (position, tag, change) = match &point {
+                StartOf(tagged) => (tagged.span.start(), tagged.tag, 1),
+                EndOf(tagged) => (tagged.span.end(), tagged.tag, -1),
+            };
+            self.counts[tag] += change;
+
+            let more_here = match self.points.peek() {
+                Some(StartOf(next)) => next.start() == position,
+                Some(EndOf(next)) => next.end() == position,
+                None => false,
+            };
+            if !more_here {
+                return Some((position.clone(), &self.counts));
+            }
+        }
+
+        None
+    }
+}
+
+
 #[cfg(test)]
 mod tests {
     use crate::points::{enumerate_points, Point::{StartOf, EndOf}};
@@ -0,0 +1,374 @@
+use crate::span::{Span, CreatableSpan};
+use crate::measure::Measurable;
+
+
+/**
+ * A span that repeats every period, ex: business hours or a weekly meeting.
+ *
+ * This is an iterator that lazily yields each occurrence in order, so it can
+ * be unbounded. Ex: with hours as the domain,
+ * `Recurrence::new(0, 24, 8).offset(9)` is 9am to 5pm every day (starting
+ * from the day at 0), giving `9–17, 33–41, 57–65, ...`.
+ *
+ * Since occurrences are sorted, a recurrence can be passed anywhere sorted
+ * spans are (ex: to sorted_chain() or n_overlapping()), though it needs to be
+ * bounded with times(), until(), or within() before anything tries to reach
+ * its end. Occurrences overlap if `duration` is longer than `period`.
+ */
+#[derive(Clone)]
+pub struct Recurrence<S: CreatableSpan> where S::Domain: Measurable {
+    // Occurrences start on a grid of `period`s from here. This can move
+    // forward along the grid when skipping ahead for within().
+    origin: S::Domain,
+
+    period: <S::Domain as Measurable>::Measure,
+    duration: <S::Domain as Measurable>::Measure,
+
+    // How many periods past `origin` the next occurrence starts, along with
+    // where that is.
+    next: Option<(u64, S::Domain)>,
+
+    // How many more occurrences may be yielded, if limited.
+    remaining: Option<usize>,
+
+    // Occurrences must start before this.
+    until: Option<S::Domain>,
+
+    // Occurrences are clipped to this.
+    window: Option<S>,
+}
+
+
+impl<S: CreatableSpan> Recurrence<S> where S::Domain: Measurable {
+    /**
+     * Occurrences lasting `duration` that start at `origin` and then every
+     * `period` after.
+     */
+    pub fn new(origin: S::Domain,
+               period: <S::Domain as Measurable>::Measure,
+               duration: <S::Domain as Measurable>::Measure) -> Self {
+        assert!(period > <S::Domain as Measurable>::Measure::default());
+        Recurrence {
+            next: Some((0, origin.clone())),
+            origin,
+            period,
+            duration,
+            remaining: None,
+            until: None,
+            window: None,
+        }
+    }
+
+    /**
+     * Starts each occurrence `by` after the start of its period, ex: to start
+     * daily business hours 9 hours after midnight.
+     */
+    pub fn offset(mut self, by: <S::Domain as Measurable>::Measure) -> Self {
+        self.origin = self.origin.offset_forward(&by);
+        self.next = self.next.map(
+            |(step, start)| (step, start.offset_forward(&by)));
+        self
+    }
+
+    /**
+     * Stops after the first `n` occurrences.
+     *
+     * These are counted from `origin` no matter where within() is called, so
+     * occurrences that within() drops still count towards `n`.
+     */
+    pub fn times(mut self, n: usize) -> Self {
+        self.remaining = Some(self.remaining.map_or(n, |current| {
+            current.min(n)
+        }));
+        self
+    }
+
+    /**
+     * Stops before the first occurrence that starts at or after `until`.
+     */
+    pub fn until(mut self, until: S::Domain) -> Self {
+        self.until = Some(match self.until {
+            Some(current) => std::cmp::min(current, until),
+            None => until,
+        });
+        self
+    }
+
+    /**
+     * Only yields the parts of occurrences within `window`.
+     *
+     * Occurrences that end before the window are skipped without visiting
+     * each of them (unless times() limits the recurrence, since then they
+     * need to be counted).
+     */
+    pub fn within<W: Span<Domain = S::Domain>>(mut self, window: &W)
+            -> Self {
+        // If there's already a window, only what's in both is kept.
+        let window = match &self.window {
+            Some(current) => {
+                let start = std::cmp::max(current.start(), window.start());
+                let end = std::cmp::min(current.end(), window.end());
+                S::new(start.clone(), std::cmp::max(start, end).clone())
+            },
+            None => S::new(window.start().clone(), window.end().clone()),
+        };
+
+        self = self.until(window.end().clone());
+        self.window = Some(window);
+        self
+    }
+
+    // Moves `next` up to the first occurrence that could overlap the window,
+    // if it's before that and the occurrences don't need to be counted.
+    fn skip_to_window(&mut self) {
+        if self.remaining.is_some() {
+            return;
+        }
+        let (Some(window), Some((_, start))) = (&self.window, &self.next)
+        else {
+            return;
+        };
+
+        // The earliest an occurrence overlapping the window can start is
+        // just after `duration` before the window does.
+        let earliest = window.start().offset_backward(&self.duration);
+        if earliest > *start {
+            self.origin = earliest.floor_to_grid(&self.origin, &self.period);
+            self.next = Some((0, self.origin.clone()));
+        }
+    }
+}
+
+
+impl<S: CreatableSpan> Iterator for Recurrence<S>
+        where S::Domain: Measurable {
+    type Item = S;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.skip_to_window();
+        loop {
+            if self.remaining == Some(0) {
+                return None;
+            }
+            let (step, start) = self.next.take()?;
+            if self.until.as_ref().is_some_and(|until| start >= *until) {
+                return None;
+            }
+
+            // Each start is worked out from `origin` rather than the start
+            // before it, so float domains don't drift. There are no more
+            // occurrences once the grid runs past the end of the domain (or
+            // stops moving forward).
+            self.next = step.checked_add(1)
+                .and_then(|step| {
+                    let next = self.origin.nth_grid_point(&self.period, step)?;
+                    Some((step, next))
+                })
+                .filter(|(_, next)| *next > start);
+            if let Some(remaining) = self.remaining.as_mut() {
+                *remaining -= 1;
+            }
+
+            let end = start.offset_forward(&self.duration);
+            match &self.window {
+                Some(window) if end <= *window.start()
+                    && start < *window.start() => continue,
+                Some(window) => return Some(S::new(
+                    std::cmp::max(&start, window.start()).clone(),
+                    std::cmp::min(&end, window.end()).clone())),
+                None => return Some(S::new(start, end)),
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::recurrence::Recurrence;
+    use crate::span::{Span, SimpleSpan, CreatableSpan};
+    use crate::vecspet::VecSpet;
+    use crate::overlapping::n_overlapping;
+    use crate::mergeiter::sorted_chain;
+
+    type SSpan = SimpleSpan<i64>;
+    type Spet = VecSpet<SSpan>;
+
+    fn spans(recurrence: Recurrence<SSpan>) -> Vec<(i64, i64)> {
+        recurrence.map(|span| (*span.start(), *span.end())).collect()
+    }
+
+    #[test]
+    fn unbounded() {
+        let first: Vec<SSpan> = Recurrence::new(5, 10, 3).take(3).collect();
+
+        assert_eq!(first, vec![
+            SSpan::new(5, 8),
+            SSpan::new(15, 18),
+            SSpan::new(25, 28),
+        ]);
+    }
+
+    #[test]
+    fn times() {
+        assert_eq!(spans(Recurrence::new(0, 7, 1).times(3)),
+                   vec![(0, 1), (7, 8), (14, 15)]);
+        assert_eq!(spans(Recurrence::new(0, 7, 1).times(0)), vec![]);
+    }
+
+    #[test]
+    fn counting_all_occurrences() {
+        // times() doesn't hide Iterator::count().
+        assert_eq!(Recurrence::<SSpan>::new(0, 7, 1).times(3).count(), 3);
+    }
+
+    #[test]
+    fn until() {
+        assert_eq!(spans(Recurrence::new(0, 7, 2).until(14)),
+                   vec![(0, 2), (7, 9)]);
+        assert_eq!(spans(Recurrence::new(0, 7, 2).until(15).times(5)),
+                   vec![(0, 2), (7, 9), (14, 16)]);
+    }
+
+    #[test]
+    fn offset() {
+        assert_eq!(spans(Recurrence::new(0, 24, 8).offset(9).times(2)),
+                   vec![(9, 17), (33, 41)]);
+    }
+
+    mod within {
+        use super::*;
+
+        #[test]
+        fn clips_to_window() {
+            let days = Recurrence::new(0, 24, 8).offset(9)
+                .within(&SSpan::new(12, 60));
+
+            assert_eq!(spans(days), vec![(12, 17), (33, 41), (57, 60)]);
+        }
+
+        #[test]
+        fn skips_ahead() {
+            // This would take far too long if each occurrence was visited.
+            let far = Recurrence::new(0, 10, 4)
+                .within(&SSpan::new(1 << 60, (1 << 60) + 20));
+
+            assert_eq!(spans(far), vec![
+                ((1 << 60) + 4, (1 << 60) + 8),
+                ((1 << 60) + 14, (1 << 60) + 18),
+            ]);
+        }
+
+        #[test]
+        fn occurrence_overlapping_window_start() {
+            let spans = spans(Recurrence::new(0, 10, 4)
+                .within(&SSpan::new(22, 35)));
+
+            assert_eq!(spans, vec![(22, 24), (30, 34)]);
+        }
+
+        #[test]
+        fn window_before_origin() {
+            assert_eq!(spans(Recurrence::new(10, 10, 4)
+                                 .within(&SSpan::new(0, 25))),
+                       vec![(10, 14), (20, 24)]);
+        }
+
+        #[test]
+        fn with_times() {
+            let counted = Recurrence::new(0, 10, 4).times(3)
+                .within(&SSpan::new(12, 100));
+
+            assert_eq!(spans(counted), vec![(12, 14), (20, 24)]);
+        }
+
+        #[test]
+        fn times_counts_from_origin() {
+            let window = SSpan::new(12, 100);
+            let times_first = Recurrence::new(0, 10, 4).times(3)
+                .within(&window);
+            let within_first = Recurrence::new(0, 10, 4).within(&window)
+                .times(3);
+
+            assert_eq!(spans(times_first), vec![(12, 14), (20, 24)]);
+            assert_eq!(spans(within_first), vec![(12, 14), (20, 24)]);
+        }
+
+        #[test]
+        fn nested_windows() {
+            let nested = Recurrence::new(0, 10, 4)
+                .within(&SSpan::new(0, 30))
+                .within(&SSpan::new(12, 100));
+
+            assert_eq!(spans(nested), vec![(12, 14), (20, 24)]);
+        }
+    }
+
+    #[test]
+    fn overlapping_occurrences() {
+        let recurrence = Recurrence::<SSpan>::new(0, 5, 8)
+            .within(&SSpan::new(0, 20));
+
+        assert_eq!(Spet::from_sorted_iter(recurrence),
+                   Spet::from_sorted_iter(vec![SSpan::new(0, 20)]));
+    }
+
+    #[test]
+    fn stops_at_end_of_domain() {
+        let near_end = Recurrence::<SimpleSpan<u8>>::new(250, 4, 2);
+
+        assert_eq!(near_end.collect::<Vec<_>>(), vec![
+            SimpleSpan::new(250, 252),
+            SimpleSpan::new(254, 255),
+        ]);
+    }
+
+    #[test]
+    fn floats() {
+        use crate::float::OrderedF64;
+        type FSpan = SimpleSpan<OrderedF64>;
+
+        let starts = |recurrence: Recurrence<FSpan>| -> Vec<f64> {
+            recurrence.map(|span| f64::from(*span.start())).collect()
+        };
+
+        let spaced =
+            Recurrence::<FSpan>::new(0.1.into(), 0.2.into(), 0.05.into());
+        assert_eq!(spaced.times(5).count(), 5);
+
+        // Adding 0.1 over and over would give a tenth start just before 1.
+        let tenths = Recurrence::new(0.0.into(), 0.1.into(), 0.05.into())
+            .until(1.0.into());
+        assert_eq!(starts(tenths.clone()).len(), 10);
+        assert_eq!(starts(tenths)[3], 3.0 * 0.1);
+    }
+
+    #[test]
+    fn business_hours_and_meetings() {
+        // Meetings over three days, in hours.
+        let meetings = Spet::from_sorted_iter(vec![
+            SSpan::new(8, 10),
+            SSpan::new(20, 22),
+            SSpan::new(40, 41),
+        ]);
+        let week = SSpan::new(0, 72);
+        let business_hours = Recurrence::new(0, 24, 8).offset(9).within(&week);
+
+        let during_hours: Spet = n_overlapping(
+            2, vec![
+                business_hours.clone().collect::<Vec<_>>(),
+                meetings.clone().into_iter().collect(),
+            ]);
+        assert_eq!(during_hours, Spet::from_sorted_iter(vec![
+            SSpan::new(9, 10),
+            SSpan::new(40, 41),
+        ]));
+
+        let busy_or_working = Spet::from_sorted_iter(sorted_chain(vec![
+            business_hours.collect::<Vec<_>>(),
+            meetings.into_iter().collect(),
+        ]));
+        assert_eq!(busy_or_working.to_string(),
+                   "{8–17, 20–22, 33–41, 57–65}");
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use crate::span::CreatableSpan;
+use crate::vecspet::VecSpet;
+use crate::measure::Measurable;
+
+
+type Measure<S> = <<S as crate::span::Span>::Domain as Measurable>::Measure;
+
+
+/**
+ * Groups point events (ex: the timestamps of requests in a server log) into
+ * sessions, where each event less than `timeout` after the one before it is
+ * part of the same session.
+ *
+ * Each session spans from its first event to its last, so a session with
+ * only one event is empty (ex: `5–5`). Empty spans are dropped by
+ * VecSpet::from_sorted_iter() and everything built on it (ex: union() or
+ * SpetMap::insert()), so lone sessions vanish as soon as the result is
+ * combined with anything. To keep them, give every session some length
+ * first (ex: with dilate()).
+ *
+ * `events` must be sorted in ascending order.
+ */
+pub fn sessionize<S, I>(events: I, timeout: Measure<S>) -> VecSpet<S>
+        where S: CreatableSpan,
+              S::Domain: Measurable,
+              I: IntoIterator<Item = S::Domain> {
+    assert!(timeout > Measure::<S>::default());
+
+    let mut sessions = Sessions::default();
+    for event in events {
+        sessions.add(event, &timeout);
+    }
+    sessions.finish()
+}
+
+
+/**
+ * Like sessionize(), but keeps separate sessions for each key (ex: for each
+ * user ID).
+ *
+ * `events` must be sorted by time for each key. Events for different keys
+ * can be interleaved however they like.
+ */
+pub fn sessionize_by_key<K, S, I>(events: I, timeout: Measure<S>)
+        -> HashMap<K, VecSpet<S>>
+        where K: Eq + Hash,
+              S: CreatableSpan,
+              S::Domain: Measurable,
+              I: IntoIterator<Item = (K, S::Domain)> {
+    assert!(timeout > Measure::<S>::default());
+
+    let mut sessions: HashMap<K, Sessions<S>> = HashMap::new();
+    for (key, event) in events {
+        sessions.entry(key).or_default().add(event, &timeout);
+    }
+
+    sessions.into_iter()
+        .map(|(key, sessions)| (key, sessions.finish()))
+        .collect()
+}
+
+
+// The sessions found so far, along with the one that's still open.
+struct Sessions<S: CreatableSpan> {
+    spans: Vec<S>,
+
+    // The first and last event of the open session.
+    open: Option<(S::Domain, S::Domain)>,
+}
+
+
+impl<S: CreatableSpan> Default for Sessions<S> {
+    fn default() -> Self {
+        Sessions { spans: Vec::new(), open: None }
+    }
+}
+
+
+impl<S: CreatableSpan> Sessions<S> where S::Domain: Measurable {
+    // Adds `event` to the open session, or starts a new session with it if
+    // it's too long after the open one.
+    fn add(&mut self, event: S::Domain, timeout: &Measure<S>) {
+        match &mut self.open {
+            Some((_, last)) if last.measure_to(&event) < *timeout => {
+                assert!(event >= *last, "events must be sorted");
+                *last = event;
+            },
+            Some((_, last)) => {
+                assert!(event >= *last, "events must be sorted");
+                let (first, last) = self.open.replace(
+                    (event.clone(), event)).unwrap();
+                self.spans.push(S::new(first, last));
+            },
+            None => self.open = Some((event.clone(), event)),
+        }
+    }
+
+    fn finish(mut self) -> VecSpet<S> {
+        if let Some((first, last)) = self.open {
+            self.spans.push(S::new(first, last));
+        }
+        VecSpet { spans: self.spans }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::sessionize::{sessionize, sessionize_by_key};
+    use crate::span::{SimpleSpan, CreatableSpan};
+    use crate::vecspet::VecSpet;
+
+    type SSpan = SimpleSpan<i64>;
+    type Spet = VecSpet<SSpan>;
+
+    fn spet(spans: &[(i64, i64)]) -> Spet {
+        VecSpet {
+            spans: spans.iter()
+                .map(|(start, end)| SSpan::new(*start, *end))
+                .collect(),
+        }
+    }
+
+    mod sessionize {
+        use super::*;
+
+        #[test]
+        fn groups_close_events() {
+            let events = vec![1, 2, 4, 10, 11, 30];
+
+            assert_eq!(sessionize(events, 5),
+                       spet(&[(1, 4), (10, 11), (30, 30)]));
+        }
+
+        #[test]
+        fn gap_equal_to_timeout_splits() {
+            assert_eq!(sessionize(vec![0, 5, 9], 5),
+                       spet(&[(0, 0), (5, 9)]));
+        }
+
+        #[test]
+        fn duplicate_events() {
+            assert_eq!(sessionize(vec![3, 3, 3], 1), spet(&[(3, 3)]));
+        }
+
+        #[test]
+        fn lone_sessions_vanish_when_combined() {
+            let sessions: Spet = sessionize(vec![1, 2, 30], 5);
+
+            assert_eq!(sessions, spet(&[(1, 2), (30, 30)]));
+            assert_eq!(sessions.union(&Spet::default()), spet(&[(1, 2)]));
+            assert_eq!(sessions.dilate(0, 1).union(&Spet::default()),
+                       spet(&[(1, 3), (30, 31)]));
+        }
+
+        #[test]
+        fn no_events() {
+            assert_eq!(sessionize::<SSpan, _>(vec![], 5), Spet::default());
+        }
+
+        #[test]
+        #[should_panic]
+        fn unsorted() {
+            sessionize::<SSpan, _>(vec![5, 3], 10);
+        }
+
+        #[test]
+        fn system_time() {
+            use std::time::{Duration, UNIX_EPOCH};
+
+            let at = |secs| UNIX_EPOCH + Duration::from_secs(secs);
+            let sessions: VecSpet<SimpleSpan<_>> = sessionize(
+                vec![at(0), at(60), at(1000)], Duration::from_secs(300));
+
+            assert_eq!(sessions, VecSpet {
+                spans: vec![
+                    SimpleSpan::new(at(0), at(60)),
+                    SimpleSpan::new(at(1000), at(1000)),
+                ],
+            });
+        }
+    }
+
+    #[test]
+    fn by_key() {
+        let events = vec![
+            ("alice", 1),
+            ("bob", 2),
+            ("alice", 3),
+            ("bob", 20),
+            ("alice", 25),
+            ("bob", 22),
+        ];
+
+        let sessions: HashMap<&str, Spet> = sessionize_by_key(events, 5);
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions["alice"], spet(&[(1, 3), (25, 25)]));
+        assert_eq!(sessions["bob"], spet(&[(2, 2), (20, 22)]));
+    }
+}
@@ -0,0 +1,355 @@
+use crate::span::CreatableSpan;
+use crate::vecspet::VecSpet;
+use crate::measure::Measurable;
+
+
+type Measure<S> = <<S as crate::span::Span>::Domain as Measurable>::Measure;
+
+
+// Walks across both spets at once, calling `visit` with each stretch between
+// consecutive starts or ends, along with whether it's in `a` and in `b`.
+fn walk<S: CreatableSpan>(a: &VecSpet<S>,
+        b: &VecSpet<S>,
+        mut visit: impl FnMut(&S::Domain, &S::Domain, bool, bool)) {
+    let mut previous: Option<(S::Domain, bool, bool)> = None;
+    VecSpet::<S>::sweep(vec![a.spans.iter(), b.spans.iter()],
+                        |position, counts| {
+        if let Some((start, in_a, in_b)) = &previous {
+            visit(start, position, *in_a, *in_b);
+        }
+        previous = Some((position.clone(), counts[0] > 0, counts[1] > 0));
+    });
+}
+
+
+/**
+ * The measures of two spets, along with those of their intersection and
+ * union.
+ *
+ * Found in a single walk over the points of both spets, without building
+ * either the intersection or the union.
+ */
+struct Measures<S: CreatableSpan> where S::Domain: Measurable {
+    a: Measure<S>,
+    b: Measure<S>,
+    intersection: Measure<S>,
+    union: Measure<S>,
+}
+
+
+impl<S: CreatableSpan> Measures<S> where S::Domain: Measurable {
+    fn of(a: &VecSpet<S>, b: &VecSpet<S>) -> Self {
+        let mut measures = Measures {
+            a: Measure::<S>::default(),
+            b: Measure::<S>::default(),
+            intersection: Measure::<S>::default(),
+            union: Measure::<S>::default(),
+        };
+
+        walk(a, b, |start, end, in_a, in_b| {
+            let measure = start.measure_to(end);
+            let add = |total: &mut Measure<S>| {
+                *total = total.clone() + measure.clone();
+            };
+            if in_a {
+                add(&mut measures.a);
+            }
+            if in_b {
+                add(&mut measures.b);
+            }
+            if in_a && in_b {
+                add(&mut measures.intersection);
+            }
+            if in_a || in_b {
+                add(&mut measures.union);
+            }
+        });
+        measures
+    }
+}
+
+
+/**
+ * The Jaccard index of two spets: `|A ∩ B| / |A ∪ B|`.
+ *
+ * This is 1 when the spets are the same and 0 when they don't overlap at all.
+ * None is returned if the union is empty.
+ */
+pub fn jaccard<S: CreatableSpan>(a: &VecSpet<S>, b: &VecSpet<S>)
+        -> Option<f64>
+        where S::Domain: Measurable {
+    let measures = Measures::of(a, b);
+    if measures.union == Default::default() {
+        None
+    } else {
+        Some(S::Domain::measure_as_f64(&measures.intersection)
+             / S::Domain::measure_as_f64(&measures.union))
+    }
+}
+
+
+/**
+ * The overlap coefficient of two spets: `|A ∩ B| / min(|A|, |B|)`.
+ *
+ * This is 1 when either spet contains the other. None is returned if either
+ * spet is empty.
+ */
+pub fn overlap_coefficient<S: CreatableSpan>(a: &VecSpet<S>, b: &VecSpet<S>)
+        -> Option<f64>
+        where S::Domain: Measurable {
+    let measures = Measures::of(a, b);
+    let smaller = std::cmp::min(measures.a, measures.b);
+    if smaller == Default::default() {
+        None
+    } else {
+        Some(S::Domain::measure_as_f64(&measures.intersection)
+             / S::Domain::measure_as_f64(&smaller))
+    }
+}
+
+
+/**
+ * The Hausdorff distance between two spets.
+ *
+ * This is the farthest that any point in one spet is from the nearest point
+ * in the other. Ex: `{0–10}` and `{0–4, 6–10}` are 1 apart (the point 5 is 1
+ * away from the second spet).
+ *
+ * None is returned if exactly one of the spets is empty (since the distance
+ * would be infinite). Two empty spets are 0 apart.
+ */
+pub fn hausdorff_distance<S: CreatableSpan>(a: &VecSpet<S>, b: &VecSpet<S>)
+        -> Option<f64>
+        where S::Domain: Measurable {
+    match (a.is_empty(), b.is_empty()) {
+        (true, true) => return Some(0.0),
+        (false, false) => (),
+        _ => return None,
+    }
+
+    // Both directions are found in the same walk.
+    let mut a_to_b = DirectedHausdorff::default();
+    let mut b_to_a = DirectedHausdorff::default();
+    walk(a, b, |start, end, in_a, in_b| {
+        a_to_b.visit(start, end, in_a, in_b);
+        b_to_a.visit(start, end, in_b, in_a);
+    });
+    Some(f64::max(a_to_b.finish(), b_to_a.finish()))
+}
+
+
+// The farthest any point in one spet (`from`) is from the nearest point in
+// another (`to`), found a stretch at a time.
+//
+// Only the parts of `from` that aren't covered by `to` matter. Each of those
+// parts sits in a gap of `to` (or before/after all of `to`), and how far
+// they are from `to` isn't known until the gap ends, so they're held until
+// then.
+struct DirectedHausdorff<T> {
+    farthest: f64,
+
+    // Where the gap of `to` that we're in started, if it's after a span.
+    left: Option<T>,
+
+    // The parts of `from` in the gap so far.
+    uncovered: Vec<(T, T)>,
+}
+
+
+impl<T> Default for DirectedHausdorff<T> {
+    fn default() -> Self {
+        DirectedHausdorff { farthest: 0.0, left: None, uncovered: Vec::new() }
+    }
+}
+
+
+impl<T: Measurable> DirectedHausdorff<T> {
+    fn visit(&mut self, start: &T, end: &T, in_from: bool, in_to: bool) {
+        if in_to {
+            self.end_gap(Some(start));
+            self.left = Some(end.clone());
+        } else if in_from {
+            self.uncovered.push((start.clone(), end.clone()));
+        }
+    }
+
+    fn end_gap(&mut self, right: Option<&T>) {
+        for (start, end) in self.uncovered.drain(..) {
+            self.farthest = self.farthest.max(farthest_in_gap(
+                &start, &end, self.left.as_ref(), right));
+        }
+    }
+
+    fn finish(mut self) -> f64 {
+        self.end_gap(None);
+        self.farthest
+    }
+}
+
+
+// The farthest any point from start to end is from the nearest of left and
+// right (where left <= start and end <= right).
+fn farthest_in_gap<T: Measurable>(
+        start: &T, end: &T, left: Option<&T>, right: Option<&T>) -> f64 {
+    match (left, right) {
+        (Some(left), Some(right)) => {
+            // The farthest point from both sides is halfway through the gap,
+            // or as near to halfway as start to end gets.
+            let gap = T::measure_as_f64(&left.measure_to(right));
+            let halfway = (gap / 2.0).max(
+                T::measure_as_f64(&left.measure_to(start))).min(
+                T::measure_as_f64(&left.measure_to(end)));
+            halfway.min(gap - halfway)
+        },
+        (Some(left), None) => T::measure_as_f64(&left.measure_to(end)),
+        (None, Some(right)) => T::measure_as_f64(&start.measure_to(right)),
+        (None, None) => f64::INFINITY,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::span::{SimpleSpan, CreatableSpan};
+    use crate::vecspet::VecSpet;
+
+    type SSpan = SimpleSpan<i32>;
+    type Spet = VecSpet<SSpan>;
+
+    fn spet(spans: &[(i32, i32)]) -> Spet {
+        Spet::from_sorted_iter(
+            spans.iter().map(|(start, end)| SSpan::new(*start, *end)))
+    }
+
+    mod jaccard {
+        use crate::similarity::jaccard;
+        use super::spet;
+
+        #[test]
+        fn identical() {
+            let a = spet(&[(1, 3), (5, 8)]);
+
+            assert_eq!(jaccard(&a, &a), Some(1.0));
+        }
+
+        #[test]
+        fn disjoint() {
+            assert_eq!(jaccard(&spet(&[(1, 3)]), &spet(&[(3, 5)])),
+                       Some(0.0));
+        }
+
+        #[test]
+        fn partial() {
+            // Intersection is 2-4 and 6-7, union is 0-8.
+            let a = spet(&[(0, 4), (6, 8)]);
+            let b = spet(&[(2, 7)]);
+
+            assert_eq!(jaccard(&a, &b), Some(3.0 / 8.0));
+        }
+
+        #[test]
+        fn both_empty() {
+            assert_eq!(jaccard(&spet(&[]), &spet(&[])), None);
+        }
+
+        #[test]
+        fn one_empty() {
+            assert_eq!(jaccard(&spet(&[(1, 2)]), &spet(&[])), Some(0.0));
+        }
+    }
+
+    mod overlap_coefficient {
+        use crate::similarity::overlap_coefficient;
+        use super::spet;
+
+        #[test]
+        fn subset() {
+            let a = spet(&[(0, 10)]);
+            let b = spet(&[(2, 3), (5, 6)]);
+
+            assert_eq!(overlap_coefficient(&a, &b), Some(1.0));
+        }
+
+        #[test]
+        fn partial() {
+            let a = spet(&[(0, 4)]);
+            let b = spet(&[(3, 11)]);
+
+            assert_eq!(overlap_coefficient(&a, &b), Some(0.25));
+        }
+
+        #[test]
+        fn one_empty() {
+            assert_eq!(overlap_coefficient(&spet(&[(1, 2)]), &spet(&[])),
+                       None);
+        }
+    }
+
+    mod hausdorff_distance {
+        use crate::similarity::hausdorff_distance;
+        use super::spet;
+
+        #[test]
+        fn identical() {
+            let a = spet(&[(1, 3), (5, 8)]);
+
+            assert_eq!(hausdorff_distance(&a, &a), Some(0.0));
+        }
+
+        #[test]
+        fn hole_in_middle() {
+            let a = spet(&[(0, 10)]);
+            let b = spet(&[(0, 4), (6, 10)]);
+
+            assert_eq!(hausdorff_distance(&a, &b), Some(1.0));
+            assert_eq!(hausdorff_distance(&b, &a), Some(1.0));
+        }
+
+        #[test]
+        fn apart() {
+            let a = spet(&[(0, 1)]);
+            let b = spet(&[(5, 6)]);
+
+            assert_eq!(hausdorff_distance(&a, &b), Some(5.0));
+        }
+
+        #[test]
+        fn overhanging_ends() {
+            let a = spet(&[(0, 10)]);
+            let b = spet(&[(2, 3)]);
+
+            assert_eq!(hausdorff_distance(&a, &b), Some(7.0));
+        }
+
+        #[test]
+        fn uncovered_part_away_from_middle_of_gap() {
+            // 10-11 is nearest to 4 (the end of 0-4), and 11 is 7 away from
+            // it while 20 is 9 away from 11.
+            let a = spet(&[(0, 4), (20, 30)]);
+            let b = spet(&[(0, 4), (10, 11), (20, 30)]);
+
+            assert_eq!(hausdorff_distance(&a, &b), Some(7.0));
+        }
+
+        #[test]
+        fn several_blockers() {
+            let a = spet(&[(0, 20)]);
+            let b = spet(&[(1, 2), (4, 5), (13, 14), (19, 20)]);
+
+            // The widest gap in b is 5-13, whose middle is 4 from either
+            // side.
+            assert_eq!(hausdorff_distance(&a, &b), Some(4.0));
+        }
+
+        #[test]
+        fn one_empty() {
+            assert_eq!(hausdorff_distance(&spet(&[(1, 2)]), &spet(&[])),
+                       None);
+        }
+
+        #[test]
+        fn both_empty() {
+            assert_eq!(hausdorff_distance(&spet(&[]), &spet(&[])), Some(0.0));
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::VecDeque;
+use std::iter::Peekable;
+use crate::span::Span;
+use crate::measure::Measurable;
+
+
+/**
+ * Measures how much of a trailing window is covered by spans, as the window
+ * slides forward.
+ *
+ * The window always has the same width and is asked about by its end (so
+ * `coverage_at(t)` looks at `t - width` to `t`). The spans must be sorted and
+ * must not overlap (ex: the spans of a VecSpet), and they're only pulled from
+ * the underlying iterator as the window reaches them, so this works on
+ * streams as well.
+ *
+ * Each span enters and leaves the window once, so sliding the window across
+ * N spans is O(N) in total no matter how many times coverage_at() is called
+ * along the way.
+ */
+pub struct SlidingCoverage<I: Iterator>
+        where I::Item: Span,
+              <I::Item as Span>::Domain: Measurable {
+    spans: Peekable<I>,
+    width: <<I::Item as Span>::Domain as Measurable>::Measure,
+
+    // The spans that overlap the window most recently asked about, along
+    // with the sum of their (unclipped) measures. Only the first and last of
+    // these can stick out past the window's edges.
+    window: VecDeque<I::Item>,
+    total: <<I::Item as Span>::Domain as Measurable>::Measure,
+
+    last_end: Option<<I::Item as Span>::Domain>,
+}
+
+
+impl<I: Iterator> SlidingCoverage<I>
+        where I::Item: Span,
+              <I::Item as Span>::Domain: Measurable {
+    /**
+     * The measure covered by spans between `end - width` and `end`.
+     *
+     * `end` must be no less than it was in the previous call.
+     */
+    pub fn coverage_at(&mut self, end: &<I::Item as Span>::Domain)
+            -> <<I::Item as Span>::Domain as Measurable>::Measure {
+        if let Some(last_end) = &self.last_end {
+            assert!(last_end <= end, "the window can only slide forward");
+        }
+        self.last_end = Some(end.clone());
+        let start = end.offset_backward(&self.width);
+
+        while let Some(span) = self.spans.next_if(|span| span.start() < end) {
+            self.total = self.total.clone() + span.measure();
+            self.window.push_back(span);
+        }
+
+        while let Some(span) = self.window.front() {
+            if span.end() > &start {
+                break;
+            }
+            self.total = self.total.clone() - span.measure();
+            self.window.pop_front();
+        }
+
+        let mut covered = self.total.clone();
+        if let Some(first) = self.window.front() {
+            if first.start() < &start {
+                covered = covered - first.start().measure_to(&start);
+            }
+        }
+        if let Some(last) = self.window.back() {
+            if last.end() > end {
+                covered = covered - end.measure_to(last.end());
+            }
+        }
+
+        covered
+    }
+}
+
+
+/**
+ * Creates a SlidingCoverage with a window `width` wide.
+ */
+pub fn sliding_coverage<I: IntoIterator>(
+        spans: I,
+        width: <<I::Item as Span>::Domain as Measurable>::Measure)
+        -> SlidingCoverage<I::IntoIter>
+        where I::Item: Span,
+              <I::Item as Span>::Domain: Measurable {
+    SlidingCoverage {
+        spans: spans.into_iter().peekable(),
+        width,
+        window: VecDeque::new(),
+        total: Default::default(),
+        last_end: None,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::sliding::sliding_coverage;
+    use crate::span::{SimpleSpan, CreatableSpan};
+    use crate::vecspet::VecSpet;
+
+    type SSpan = SimpleSpan<i32>;
+
+    fn example() -> VecSpet<SSpan> {
+        VecSpet::from_sorted_iter(vec![
+            SSpan::new(2, 4),
+            SSpan::new(5, 6),
+            SSpan::new(8, 20),
+        ])
+    }
+
+    #[test]
+    fn slides_across_spans() {
+        let spet = example();
+        let mut coverage = sliding_coverage(&spet, 5);
+
+        let measured: Vec<_> = (0..25).map(|t| coverage.coverage_at(&t))
+            .collect();
+        assert_eq!(measured, vec![
+            0, 0, 0, 1, 2, 2, 3, 3, 2, 2, 3, 3, 4, 5, 5, 5, 5, 5, 5, 5, 5,
+            4, 3, 2, 1,
+        ]);
+    }
+
+    #[test]
+    fn matches_clipping() {
+        let spet = example();
+        let mut coverage = sliding_coverage(&spet, 7);
+
+        for end in 0..30 {
+            let window = SSpan::new(end - 7, end);
+            assert_eq!(coverage.coverage_at(&end),
+                       spet.clip(&window).measure());
+        }
+    }
+
+    #[test]
+    fn repeated_and_skipped_positions() {
+        let spet = example();
+        let mut coverage = sliding_coverage(&spet, 3);
+
+        assert_eq!(coverage.coverage_at(&4), 2);
+        assert_eq!(coverage.coverage_at(&4), 2);
+        assert_eq!(coverage.coverage_at(&100), 0);
+    }
+
+    #[test]
+    fn window_inside_span() {
+        let spet = example();
+        let mut coverage = sliding_coverage(&spet, 2);
+
+        assert_eq!(coverage.coverage_at(&15), 2);
+    }
+
+    #[test]
+    fn streaming_source() {
+        // Spans generated on the fly rather than stored in a spet.
+        let spans = (0..).map(|i| SSpan::new(i * 10, i * 10 + 5));
+        let mut coverage = sliding_coverage(spans, 20);
+
+        assert_eq!(coverage.coverage_at(&1_000_000), 10);
+    }
+
+    #[test]
+    #[should_panic]
+    fn backwards() {
+        let spet = example();
+        let mut coverage = sliding_coverage(&spet, 2);
+
+        coverage.coverage_at(&5);
+        coverage.coverage_at(&4);
+    }
+}
@@ -0,0 +1,199 @@
+use crate::span::{Span, CreatableSpan};
+use crate::points::Sweep;
+use crate::measure::Measurable;
+
+
+type Measure<S> = <<S as Span>::Domain as Measurable>::Measure;
+type Sources<'a, S> = Box<dyn Iterator<Item = S> + 'a>;
+
+
+/**
+ * Finds windows for a meeting, ex: where at most `max_busy` of the people
+ * with the `busy` spets are busy for at least `duration` during
+ * `working_hours`.
+ *
+ * `busy` takes spets the same way n_overlapping() does, one per person, and
+ * `working_hours` is a sorted iterator of spans (ex: a Recurrence). Each
+ * window is as long as it can be, so a meeting fits anywhere in it that
+ * leaves `duration` before its end.
+ *
+ * Windows are found lazily in chronological order, so `.next()` gives the
+ * earliest and `.take(n)` the first n in time (not the n longest or least
+ * busy). If `working_hours` is unbounded and there are no more windows,
+ * looking for another never finishes.
+ */
+pub fn find_slots<'a, S, B, W>(busy: B,
+                               working_hours: W,
+                               duration: Measure<S>,
+                               max_busy: usize) -> Slots<'a, S>
+        where S: CreatableSpan + 'a,
+              S::Domain: Measurable,
+              B: IntoIterator,
+              B::Item: IntoIterator,
+              <B::Item as IntoIterator>::IntoIter: 'a,
+              <B::Item as IntoIterator>::Item: Span<Domain = S::Domain>,
+              W: IntoIterator,
+              W::IntoIter: 'a,
+              W::Item: Span<Domain = S::Domain> {
+    fn boxed<'a, S: CreatableSpan + 'a, T: Span<Domain = S::Domain>>(
+            spans: impl Iterator<Item = T> + 'a) -> Sources<'a, S> {
+        Box::new(spans.map(
+            |span| S::new(span.start().clone(), span.end().clone())))
+    }
+
+    // Working hours are swept first, followed by each person's spet.
+    let mut sources = vec![boxed(working_hours.into_iter())];
+    for spans in busy {
+        sources.push(boxed(spans.into_iter()));
+    }
+
+    Slots {
+        sweep: Sweep::new(sources),
+        duration,
+        max_busy,
+        open: None,
+    }
+}
+
+
+/**
+ * The windows found by find_slots().
+ */
+pub struct Slots<'a, S: CreatableSpan + 'a> where S::Domain: Measurable {
+    sweep: Sweep<Sources<'a, S>>,
+    duration: Measure<S>,
+    max_busy: usize,
+
+    // The start of the window we're in, if we're in one.
+    open: Option<S::Domain>,
+}
+
+
+impl<'a, S: CreatableSpan + 'a> Iterator for Slots<'a, S>
+        where S::Domain: Measurable {
+    type Item = S;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((position, counts)) = self.sweep.next_position() {
+            // Working hours can overlap, as can the spans of one person.
+            let num_busy = counts[1..].iter()
+                .filter(|count| **count > 0)
+                .count();
+            let free = counts[0] > 0 && num_busy <= self.max_busy;
+
+            match (self.open.take(), free) {
+                (Some(start), true) => self.open = Some(start),
+                (Some(start), false) => {
+                    if start.measure_to(&position) >= self.duration {
+                        return Some(S::new(start, position));
+                    }
+                },
+                (None, true) => self.open = Some(position),
+                (None, false) => (),
+            }
+        }
+
+        None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::slots::find_slots;
+    use crate::span::{Span, SimpleSpan, CreatableSpan};
+    use crate::vecspet::VecSpet;
+    use crate::recurrence::Recurrence;
+
+    type SSpan = SimpleSpan<i64>;
+    type Spet = VecSpet<SSpan>;
+
+    fn spet(spans: &[(i64, i64)]) -> Spet {
+        Spet::from_sorted_iter(
+            spans.iter().map(|(start, end)| SSpan::new(*start, *end)))
+    }
+
+    fn spans(slots: impl Iterator<Item = SSpan>) -> Vec<(i64, i64)> {
+        slots.map(|span| (*span.start(), *span.end())).collect()
+    }
+
+    // Two people over days of 24 hours, working 9 to 5.
+    fn example() -> (Vec<Spet>, Recurrence<SSpan>) {
+        let busy = vec![
+            spet(&[(9, 11), (14, 15)]),
+            spet(&[(10, 12), (33, 40)]),
+        ];
+        (busy, Recurrence::new(0, 24, 8).offset(9))
+    }
+
+    #[test]
+    fn earliest() {
+        let (busy, working_hours) = example();
+
+        assert_eq!(spans(find_slots(&busy, working_hours, 2, 0).take(1)),
+                   vec![(12, 14)]);
+    }
+
+    #[test]
+    fn first_few() {
+        let (busy, working_hours) = example();
+
+        assert_eq!(spans(find_slots(&busy, working_hours, 2, 0).take(3)),
+                   vec![(12, 14), (15, 17), (57, 65)]);
+    }
+
+    #[test]
+    fn some_busy() {
+        let (busy, working_hours) = example();
+
+        assert_eq!(spans(find_slots(&busy, working_hours, 2, 1).take(3)),
+                   vec![(11, 17), (33, 41), (57, 65)]);
+    }
+
+    #[test]
+    fn too_short() {
+        let (busy, working_hours) = example();
+
+        assert_eq!(spans(find_slots(&busy, working_hours, 3, 0).take(2)),
+                   vec![(57, 65), (81, 89)]);
+    }
+
+    #[test]
+    fn bounded_working_hours() {
+        let busy = vec![spet(&[(2, 4)])];
+        let working_hours = vec![SSpan::new(0, 3), SSpan::new(3, 10)];
+
+        assert_eq!(spans(find_slots(&busy, working_hours, 1, 0)),
+                   vec![(0, 2), (4, 10)]);
+    }
+
+    #[test]
+    fn overlapping_busy_spans_are_one_person() {
+        let busy = vec![
+            vec![SSpan::new(1, 5), SSpan::new(2, 6)],
+            vec![SSpan::new(7, 8)],
+        ];
+        let working_hours = vec![SSpan::new(0, 10)];
+
+        assert_eq!(spans(find_slots(busy, working_hours, 1, 1)),
+                   vec![(0, 10)]);
+    }
+
+    #[test]
+    fn empty_busy_spans() {
+        let busy = vec![spet(&[(3, 3)]), spet(&[(6, 6), (8, 9)])];
+        let working_hours = vec![SSpan::new(0, 10)];
+
+        assert_eq!(spans(find_slots(&busy, working_hours, 1, 0)),
+                   vec![(0, 8), (9, 10)]);
+    }
+
+    #[test]
+    fn no_one_busy() {
+        let busy: Vec<Spet> = Vec::new();
+        let working_hours = vec![SSpan::new(0, 10)];
+
+        assert_eq!(spans(find_slots(&busy, working_hours, 5, 0)),
+                   vec![(0, 10)]);
+    }
+}
@@ -1,3 +1,74 @@
+use std::cmp::Ordering;
+use crate::measure::Measurable;
+
+
+/**
+ * One of the 13 relations of Allen's interval algebra.
+ *
+ * `a.relation(&b)` gives the relation `R` such that "a R b" holds (ex: if `a`
+ * is `1–2` and `b` is `2–5` then `a` Meets `b`).
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum AllenRelation {
+    Before,
+    Meets,
+    Overlaps,
+    Starts,
+    During,
+    Finishes,
+    Equals,
+    FinishedBy,
+    Contains,
+    StartedBy,
+    OverlappedBy,
+    MetBy,
+    After,
+}
+
+
+impl AllenRelation {
+    pub const ALL: [AllenRelation; 13] = [
+        AllenRelation::Before,
+        AllenRelation::Meets,
+        AllenRelation::Overlaps,
+        AllenRelation::Starts,
+        AllenRelation::During,
+        AllenRelation::Finishes,
+        AllenRelation::Equals,
+        AllenRelation::FinishedBy,
+        AllenRelation::Contains,
+        AllenRelation::StartedBy,
+        AllenRelation::OverlappedBy,
+        AllenRelation::MetBy,
+        AllenRelation::After,
+    ];
+
+    /**
+     * The relation that holds when the operands are swapped.
+     *
+     * `a.relation(&b).inverse() == b.relation(&a)` for any spans `a` and `b`.
+     */
+    pub fn inverse(self) -> AllenRelation {
+        use AllenRelation::*;
+        match self {
+            Before => After,
+            Meets => MetBy,
+            Overlaps => OverlappedBy,
+            Starts => StartedBy,
+            During => Contains,
+            Finishes => FinishedBy,
+            Equals => Equals,
+            FinishedBy => Finishes,
+            Contains => During,
+            StartedBy => Starts,
+            OverlappedBy => Overlaps,
+            MetBy => Meets,
+            After => Before,
+        }
+    }
+}
+
+
 pub trait Span: Ord + Clone {
     type Domain: Ord + Clone;
 
@@ -6,6 +77,55 @@ pub trait Span: Ord + Clone {
     fn contains(&self, query: &Self::Domain) -> bool {
         self.start() <= query && query < self.end()
     }
+
+    /**
+     * The measure from this span's start to its end (ex: its duration).
+     */
+    fn measure(&self) -> <Self::Domain as Measurable>::Measure
+            where Self::Domain: Measurable {
+        self.start().measure_to(self.end())
+    }
+
+    /**
+     * Finds the Allen relation between this span and `other`.
+     *
+     * Allen's algebra is defined for spans with a non-zero length, and
+     * zero-length spans (which n_overlapping can produce) would otherwise
+     * satisfy several relations at once. Those ties are broken by preferring
+     * Equals, then Before/After, then Meets/MetBy. So `5–5` Meets `5–8`
+     * (rather than Starts it) and `8–8` is MetBy `5–8`. This keeps
+     * `a.relation(&b) == b.relation(&a).inverse()` true for all spans.
+     */
+    fn relation<O: Span<Domain = Self::Domain>>(&self, other: &O)
+            -> AllenRelation {
+        use AllenRelation::*;
+        let starts = self.start().cmp(other.start());
+        let ends = self.end().cmp(other.end());
+
+        if starts == Ordering::Equal && ends == Ordering::Equal {
+            return Equals;
+        }
+
+        match (self.end().cmp(other.start()), self.start().cmp(other.end())) {
+            (Ordering::Less, _) => return Before,
+            (_, Ordering::Greater) => return After,
+            (Ordering::Equal, _) => return Meets,
+            (_, Ordering::Equal) => return MetBy,
+            _ => {},
+        }
+
+        match (starts, ends) {
+            (Ordering::Less, Ordering::Less) => Overlaps,
+            (Ordering::Less, Ordering::Equal) => FinishedBy,
+            (Ordering::Less, Ordering::Greater) => Contains,
+            (Ordering::Equal, Ordering::Less) => Starts,
+            (Ordering::Equal, Ordering::Greater) => StartedBy,
+            (Ordering::Greater, Ordering::Less) => During,
+            (Ordering::Greater, Ordering::Equal) => Finishes,
+            (Ordering::Greater, Ordering::Greater) => OverlappedBy,
+            (Ordering::Equal, Ordering::Equal) => unreachable!(),
+        }
+    }
 }
 
 
@@ -29,7 +149,7 @@ impl<T: Ord + Copy> Span for SimpleSpan<T> {
 }
 
 
-impl<'a, T: Span> Span for &'a T {
+impl<T: Span> Span for &T {
     type Domain = T::Domain;
 
     fn start(&self) -> &Self::Domain {
@@ -42,6 +162,33 @@ impl<'a, T: Span> Span for &'a T {
 }
 
 
+/**
+ * A span along with a tag saying where it came from.
+ *
+ * Tagged spans are ordered by their span first, so tagging every span in a
+ * sorted iterator keeps it sorted. This lets a sweep over the points of
+ * several spets (ex: VecSpet::diff) tell which spet each point belongs to.
+ */
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub(crate) struct Tagged<S: Span, T: Ord + Clone> {
+    pub span: S,
+    pub tag: T,
+}
+
+
+impl<S: Span, T: Ord + Clone> Span for Tagged<S, T> {
+    type Domain = S::Domain;
+
+    fn start(&self) -> &Self::Domain {
+        self.span.start()
+    }
+
+    fn end(&self) -> &Self::Domain {
+        self.span.end()
+    }
+}
+
+
 // If I put new() on the Span trait, I wouldn't be able to have all
 // references to a Span also implement Span, since I couldn't have a function
 // that returns a reference to an object it allocated.
@@ -55,3 +202,233 @@ impl<T: Ord + Copy> CreatableSpan for SimpleSpan<T> {
         SimpleSpan { start, end }
     }
 }
+
+
+impl<T: Ord + Copy + Measurable> SimpleSpan<T> {
+    /**
+     * Creates the span from `start` to `start + length`.
+     *
+     * Ex: `SimpleSpan::starting_at(now, Duration::from_secs(30))` is the 30
+     * seconds starting at `now`.
+     */
+    pub fn starting_at(start: T, length: T::Measure) -> Self {
+        SimpleSpan { start, end: start.offset_forward(&length) }
+    }
+
+    /**
+     * Creates the span from `end - length` to `end`.
+     */
+    pub fn ending_at(end: T, length: T::Measure) -> Self {
+        SimpleSpan { start: end.offset_backward(&length), end }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    mod measure {
+        use crate::span::{Span, SimpleSpan, CreatableSpan};
+
+        #[test]
+        fn simple() {
+            assert_eq!(SimpleSpan::new(3, 7).measure(), 4);
+        }
+
+        #[test]
+        fn starting_at() {
+            assert_eq!(SimpleSpan::starting_at(3, 4), SimpleSpan::new(3, 7));
+        }
+
+        #[test]
+        fn ending_at() {
+            assert_eq!(SimpleSpan::ending_at(7, 4), SimpleSpan::new(3, 7));
+        }
+
+        #[test]
+        fn zero_length() {
+            assert_eq!(SimpleSpan::new(3, 3).measure(), 0);
+        }
+    }
+
+    mod relation {
+        use crate::span::{Span, SimpleSpan, CreatableSpan, AllenRelation};
+        use AllenRelation::*;
+
+        type SSpan = SimpleSpan<i32>;
+
+        fn check(a: (i32, i32), b: (i32, i32), expected: AllenRelation) {
+            let a = SSpan::new(a.0, a.1);
+            let b = SSpan::new(b.0, b.1);
+            assert_eq!(a.relation(&b), expected);
+            assert_eq!(b.relation(&a), expected.inverse());
+        }
+
+        #[test]
+        fn before() {
+            check((1, 2), (3, 5), Before);
+        }
+
+        #[test]
+        fn meets() {
+            check((1, 3), (3, 5), Meets);
+        }
+
+        #[test]
+        fn overlaps() {
+            check((1, 4), (3, 5), Overlaps);
+        }
+
+        #[test]
+        fn starts() {
+            check((1, 3), (1, 5), Starts);
+        }
+
+        #[test]
+        fn during() {
+            check((2, 3), (1, 5), During);
+        }
+
+        #[test]
+        fn finishes() {
+            check((3, 5), (1, 5), Finishes);
+        }
+
+        #[test]
+        fn equals() {
+            check((1, 5), (1, 5), Equals);
+        }
+
+        #[test]
+        fn finished_by() {
+            check((1, 5), (3, 5), FinishedBy);
+        }
+
+        #[test]
+        fn contains() {
+            check((1, 5), (2, 3), Contains);
+        }
+
+        #[test]
+        fn started_by() {
+            check((1, 5), (1, 3), StartedBy);
+        }
+
+        #[test]
+        fn overlapped_by() {
+            check((3, 5), (1, 4), OverlappedBy);
+        }
+
+        #[test]
+        fn met_by() {
+            check((3, 5), (1, 3), MetBy);
+        }
+
+        #[test]
+        fn after() {
+            check((3, 5), (1, 2), After);
+        }
+
+        #[test]
+        fn zero_length_equals() {
+            check((3, 3), (3, 3), Equals);
+        }
+
+        #[test]
+        fn zero_length_at_start() {
+            check((1, 1), (1, 5), Meets);
+        }
+
+        #[test]
+        fn zero_length_at_end() {
+            check((5, 5), (1, 5), MetBy);
+        }
+
+        #[test]
+        fn zero_length_inside() {
+            check((3, 3), (1, 5), During);
+        }
+
+        #[test]
+        fn zero_length_outside() {
+            check((0, 0), (1, 5), Before);
+            check((7, 7), (1, 5), After);
+        }
+
+        #[test]
+        fn zero_length_neighbors() {
+            check((1, 1), (2, 2), Before);
+        }
+
+        #[test]
+        fn inverse_is_involution() {
+            for relation in AllenRelation::ALL.iter() {
+                assert_eq!(relation.inverse().inverse(), *relation);
+            }
+        }
+
+        // Allen's definitions of each relation, written out independently of
+        // Span::relation().
+        fn holds(relation: AllenRelation, a: (i32, i32), b: (i32, i32))
+                -> bool {
+            let ((a_start, a_end), (b_start, b_end)) = (a, b);
+            match relation {
+                Before => a_end < b_start,
+                Meets => a_end == b_start,
+                Overlaps => a_start < b_start && b_start < a_end
+                    && a_end < b_end,
+                Starts => a_start == b_start && a_end < b_end,
+                During => b_start < a_start && a_end < b_end,
+                Finishes => b_start < a_start && a_end == b_end,
+                Equals => a == b,
+                _ => holds(relation.inverse(), b, a),
+            }
+        }
+
+        #[test]
+        fn exactly_one_relation_holds() {
+            // Allen's relations are exclusive for spans that aren't empty.
+            for a_start in 0..5 {
+                for a_end in a_start + 1..5 {
+                    for b_start in 0..5 {
+                        for b_end in b_start + 1..5 {
+                            let (a, b) = ((a_start, a_end), (b_start, b_end));
+                            let holding: Vec<AllenRelation> =
+                                AllenRelation::ALL.iter()
+                                    .cloned()
+                                    .filter(|relation| holds(*relation, a, b))
+                                    .collect();
+
+                            let relation = SSpan::new(a.0, a.1)
+                                .relation(&SSpan::new(b.0, b.1));
+                            assert_eq!(holding, vec![relation]);
+                        }
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn mirrored_relations_agree() {
+            // Every pair of spans drawn from a small grid (including the
+            // zero-length ones) must agree with its mirrored pair.
+            let mut seen = Vec::new();
+            for a_start in 0..4 {
+                for a_end in a_start..4 {
+                    for b_start in 0..4 {
+                        for b_end in b_start..4 {
+                            let a = SSpan::new(a_start, a_end);
+                            let b = SSpan::new(b_start, b_end);
+                            let relation = a.relation(&b);
+                            assert_eq!(b.relation(&a), relation.inverse());
+                            if !seen.contains(&relation) {
+                                seen.push(relation);
+                            }
+                        }
+                    }
+                }
+            }
+
+            assert_eq!(seen.len(), AllenRelation::ALL.len());
+        }
+    }
+}
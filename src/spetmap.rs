@@ -0,0 +1,391 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::iter::FromIterator;
+use crate::span::CreatableSpan;
+use crate::vecspet::VecSpet;
+
+
+/**
+ * A spet for each key, ex: the times each user was online.
+ *
+ * Keys whose spet would be empty aren't stored, so every spet in the map has
+ * at least one span.
+ */
+#[derive(Clone, PartialEq, Eq)]
+pub struct SpetMap<K: Ord, S: CreatableSpan> {
+    spets: BTreeMap<K, VecSpet<S>>,
+}
+
+
+impl<K: Ord, S: CreatableSpan> Default for SpetMap<K, S> {
+    fn default() -> Self {
+        SpetMap { spets: BTreeMap::new() }
+    }
+}
+
+
+impl<K: Ord + Debug, S: CreatableSpan + Debug> Debug for SpetMap<K, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.spets.iter()).finish()
+    }
+}
+
+
+impl<K: Ord, S: CreatableSpan> SpetMap<K, S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * Adds `span` to the spet for `key`.
+     */
+    pub fn insert(&mut self, key: K, span: S) {
+        self.insert_spet(key, &VecSpet::from_sorted_iter(vec![span]));
+    }
+
+    /**
+     * Adds everything in `spet` to the spet for `key`.
+     */
+    pub fn insert_spet(&mut self, key: K, spet: &VecSpet<S>) {
+        if spet.is_empty() {
+            return;
+        }
+
+        let existing = self.spets.entry(key).or_default();
+        *existing = existing.union(spet);
+    }
+
+    pub fn get(&self, key: &K) -> Option<&VecSpet<S>> {
+        self.spets.get(key)
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<VecSpet<S>> {
+        self.spets.remove(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.spets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spets.is_empty()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.spets.keys()
+    }
+
+    /**
+     * Iterates over each key and its spet, in ascending order of keys.
+     */
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &VecSpet<S>)> {
+        self.spets.iter()
+    }
+
+    /**
+     * Everything covered by any key's spet.
+     */
+    pub fn union_all(&self) -> VecSpet<S> {
+        VecSpet::from_sorted_iter(
+            crate::mergeiter::sorted_chain(self.spets.values()))
+    }
+
+    /**
+     * Everything covered by every key's spet. Empty if the map is.
+     */
+    pub fn intersection_all(&self) -> VecSpet<S> {
+        if self.spets.is_empty() {
+            return VecSpet::default();
+        }
+        crate::overlapping::n_overlapping(self.spets.len(), self.spets.values())
+    }
+
+    /**
+     * Finds where at least `n` keys' spets overlap, along with which keys
+     * they are.
+     *
+     * Gives sorted spans, each with the (ascending) keys covering all of it.
+     * A new span starts whenever the set of keys changes, so spans can
+     * touch.
+     */
+    pub fn n_overlapping(&self, n: usize) -> Vec<(S, Vec<&K>)> {
+        assert!(n > 0);
+        let keys: Vec<&K> = self.spets.keys().collect();
+
+        let mut result: Vec<(S, Vec<&K>)> = Vec::new();
+        let mut pending: Option<(S::Domain, Vec<usize>)> = None;
+        let spans = self.spets.values().map(|spet| spet.into_iter()).collect();
+        VecSpet::<S>::sweep(spans, |position, counts| {
+            let covering: Vec<usize> = (0..counts.len())
+                .filter(|i| counts[*i] > 0)
+                .collect();
+            if pending.as_ref().is_some_and(|(_, keys)| *keys == covering) {
+                return;
+            }
+
+            if let Some((start, covered)) = pending.take() {
+                result.push((
+                    S::new(start, position.clone()),
+                    covered.into_iter().map(|i| keys[i]).collect(),
+                ));
+            }
+            if covering.len() >= n {
+                pending = Some((position.clone(), covering));
+            }
+        });
+
+        result
+    }
+
+    /**
+     * Key-wise union, keeping every key in either map.
+     */
+    pub fn union(&self, other: &SpetMap<K, S>) -> SpetMap<K, S>
+            where K: Clone {
+        let mut result = self.clone();
+        for (key, spet) in other.spets.iter() {
+            result.insert_spet(key.clone(), spet);
+        }
+        result
+    }
+
+    /**
+     * Key-wise intersection, keeping only keys in both maps (and whose
+     * intersection isn't empty).
+     */
+    pub fn intersection(&self, other: &SpetMap<K, S>) -> SpetMap<K, S>
+            where K: Clone {
+        self.zip_with(other, |spet, other| {
+            other.map(|other| spet.intersection(other))
+        })
+    }
+
+    /**
+     * Key-wise difference: for each key in this map, everything in its spet
+     * that isn't in `other`'s spet for that key.
+     */
+    pub fn difference(&self, other: &SpetMap<K, S>) -> SpetMap<K, S>
+            where K: Clone {
+        self.zip_with(other, |spet, other| match other {
+            Some(other) => Some(spet.difference(other)),
+            None => Some(spet.clone()),
+        })
+    }
+
+    // Builds a map from each of this map's keys to what `f` gives for its
+    // spet and `other`'s spet for the same key. Keys are dropped if `f`
+    // gives None or an empty spet.
+    fn zip_with(&self,
+            other: &SpetMap<K, S>,
+            f: impl Fn(&VecSpet<S>, Option<&VecSpet<S>>) -> Option<VecSpet<S>>)
+            -> SpetMap<K, S>
+            where K: Clone {
+        SpetMap {
+            spets: self.spets.iter()
+                .filter_map(|(key, spet)| {
+                    f(spet, other.spets.get(key))
+                        .filter(|spet| !spet.is_empty())
+                        .map(|spet| (key.clone(), spet))
+                })
+                .collect(),
+        }
+    }
+}
+
+
+impl<K: Ord, S: CreatableSpan> FromIterator<(K, S)> for SpetMap<K, S> {
+    /**
+     * Builds a map from `(key, span)` pairs in any order.
+     */
+    fn from_iter<I: IntoIterator<Item = (K, S)>>(iter: I) -> Self {
+        let mut spans: BTreeMap<K, Vec<S>> = BTreeMap::new();
+        for (key, span) in iter {
+            spans.entry(key).or_default().push(span);
+        }
+
+        SpetMap {
+            spets: spans.into_iter()
+                .map(|(key, mut spans)| {
+                    spans.sort();
+                    (key, VecSpet::from_sorted_iter(spans))
+                })
+                .filter(|(_, spet)| !spet.is_empty())
+                .collect(),
+        }
+    }
+}
+
+
+impl<K: Ord, S: CreatableSpan> IntoIterator for SpetMap<K, S> {
+    type Item = (K, VecSpet<S>);
+    type IntoIter = std::collections::btree_map::IntoIter<K, VecSpet<S>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.spets.into_iter()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::spetmap::SpetMap;
+    use crate::span::{SimpleSpan, CreatableSpan};
+    use crate::vecspet::VecSpet;
+
+    type SSpan = SimpleSpan<i64>;
+    type Spet = VecSpet<SSpan>;
+    type Map = SpetMap<&'static str, SSpan>;
+
+    fn spet(spans: &[(i64, i64)]) -> Spet {
+        Spet::from_sorted_iter(
+            spans.iter().map(|(start, end)| SSpan::new(*start, *end)))
+    }
+
+    fn map(entries: &[(&'static str, i64, i64)]) -> Map {
+        entries.iter()
+            .map(|(key, start, end)| (*key, SSpan::new(*start, *end)))
+            .collect()
+    }
+
+    mod insert {
+        use super::*;
+
+        #[test]
+        fn unions_per_key() {
+            let mut a = Map::new();
+            a.insert("alice", SSpan::new(5, 8));
+            a.insert("alice", SSpan::new(1, 3));
+            a.insert("alice", SSpan::new(2, 4));
+            a.insert("bob", SSpan::new(0, 1));
+
+            assert_eq!(a.len(), 2);
+            assert_eq!(a.get(&"alice"), Some(&spet(&[(1, 4), (5, 8)])));
+            assert_eq!(a.get(&"bob"), Some(&spet(&[(0, 1)])));
+            assert_eq!(a.get(&"carol"), None);
+        }
+
+        #[test]
+        fn empty_spets_are_skipped() {
+            let mut a = Map::new();
+            a.insert_spet("alice", &Spet::default());
+
+            assert!(a.is_empty());
+        }
+    }
+
+    #[test]
+    fn from_iter() {
+        let a = map(&[("bob", 4, 6), ("alice", 2, 3), ("bob", 1, 5)]);
+
+        assert_eq!(a.keys().collect::<Vec<_>>(), vec![&"alice", &"bob"]);
+        assert_eq!(a.get(&"bob"), Some(&spet(&[(1, 6)])));
+    }
+
+    #[test]
+    fn union_all() {
+        let a = map(&[("alice", 1, 3), ("bob", 2, 5), ("carol", 7, 8)]);
+
+        assert_eq!(a.union_all(), spet(&[(1, 5), (7, 8)]));
+        assert_eq!(Map::new().union_all(), Spet::default());
+    }
+
+    #[test]
+    fn intersection_all() {
+        let a = map(&[("alice", 1, 6), ("bob", 2, 5), ("carol", 4, 8)]);
+
+        assert_eq!(a.intersection_all(), spet(&[(4, 5)]));
+        assert_eq!(Map::new().intersection_all(), Spet::default());
+    }
+
+    mod n_overlapping {
+        use super::*;
+
+        #[test]
+        fn attributed() {
+            let a = map(&[
+                ("alice", 1, 6),
+                ("bob", 2, 4),
+                ("carol", 3, 8),
+                ("dave", 10, 12),
+            ]);
+
+            assert_eq!(a.n_overlapping(2), vec![
+                (SSpan::new(2, 3), vec![&"alice", &"bob"]),
+                (SSpan::new(3, 4), vec![&"alice", &"bob", &"carol"]),
+                (SSpan::new(4, 6), vec![&"alice", &"carol"]),
+            ]);
+            assert_eq!(a.n_overlapping(3), vec![
+                (SSpan::new(3, 4), vec![&"alice", &"bob", &"carol"]),
+            ]);
+        }
+
+        #[test]
+        fn one_key_at_a_time() {
+            let a = map(&[("alice", 1, 3), ("bob", 3, 5), ("bob", 7, 8)]);
+
+            assert_eq!(a.n_overlapping(1), vec![
+                (SSpan::new(1, 3), vec![&"alice"]),
+                (SSpan::new(3, 5), vec![&"bob"]),
+                (SSpan::new(7, 8), vec![&"bob"]),
+            ]);
+        }
+
+        #[test]
+        fn matches_unattributed() {
+            let a = map(&[
+                ("alice", 1, 6),
+                ("bob", 2, 4),
+                ("bob", 5, 9),
+                ("carol", 3, 8),
+            ]);
+
+            let spans: Vec<SSpan> = a.n_overlapping(2).into_iter()
+                .map(|(span, _)| span)
+                .collect();
+            assert_eq!(Spet::coalesce(spans),
+                       crate::overlapping::n_overlapping(
+                           2, a.iter().map(|(_, spet)| spet)));
+        }
+    }
+
+    mod key_wise {
+        use super::*;
+
+        fn example() -> (Map, Map) {
+            let a = map(&[("alice", 1, 6), ("bob", 2, 4), ("carol", 0, 2)]);
+            let b = map(&[("alice", 3, 4), ("bob", 0, 9), ("dave", 5, 6)]);
+            (a, b)
+        }
+
+        #[test]
+        fn union() {
+            let (a, b) = example();
+
+            assert_eq!(a.union(&b), map(&[
+                ("alice", 1, 6),
+                ("bob", 0, 9),
+                ("carol", 0, 2),
+                ("dave", 5, 6),
+            ]));
+        }
+
+        #[test]
+        fn intersection() {
+            let (a, b) = example();
+
+            assert_eq!(a.intersection(&b),
+                       map(&[("alice", 3, 4), ("bob", 2, 4)]));
+        }
+
+        #[test]
+        fn difference() {
+            let (a, b) = example();
+
+            assert_eq!(a.difference(&b), map(&[
+                ("alice", 1, 3),
+                ("alice", 4, 6),
+                ("carol", 0, 2),
+            ]));
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use std::time::{Duration, Instant, SystemTime};
+use crate::measure::Measurable;
+
+
+// Duration can only be multiplied by a u32, so grid steps are worked out in
+// nanoseconds and converted back.
+fn duration_from_nanos(nanos: u128) -> Duration {
+    const NANOS_PER_SEC: u128 = 1_000_000_000;
+    Duration::new((nanos / NANOS_PER_SEC) as u64,
+                  (nanos % NANOS_PER_SEC) as u32)
+}
+
+
+fn grid_offset(since_origin: Duration, width: &Duration) -> Duration {
+    let width = width.as_nanos();
+    duration_from_nanos(since_origin.as_nanos() / width * width)
+}
+
+
+// `width * n`, or None if that's more than a Duration can hold.
+fn grid_span(width: &Duration, n: u64) -> Option<Duration> {
+    let nanos = width.as_nanos().checked_mul(n as u128)?;
+    if nanos > Duration::MAX.as_nanos() {
+        return None;
+    }
+    Some(duration_from_nanos(nanos))
+}
+
+
+/**
+ * Offsets panic if they move past what a SystemTime can represent.
+ */
+impl Measurable for SystemTime {
+    type Measure = Duration;
+
+    fn measure_to(&self, later: &Self) -> Duration {
+        later.duration_since(*self).unwrap_or_default()
+    }
+
+    fn offset_forward(&self, by: &Duration) -> Self {
+        *self + *by
+    }
+
+    fn offset_backward(&self, by: &Duration) -> Self {
+        *self - *by
+    }
+
+    fn checked_offset_forward(&self, by: &Duration) -> Option<Self> {
+        self.checked_add(*by)
+    }
+
+    fn checked_offset_backward(&self, by: &Duration) -> Option<Self> {
+        self.checked_sub(*by)
+    }
+
+    fn measure_as_f64(measure: &Duration) -> f64 {
+        measure.as_secs_f64()
+    }
+
+    fn floor_to_grid(&self, origin: &Self, width: &Duration) -> Self {
+        *origin + grid_offset(origin.measure_to(self), width)
+    }
+
+    fn nth_grid_point(&self, width: &Duration, n: u64) -> Option<Self> {
+        self.checked_add(grid_span(width, n)?)
+    }
+}
+
+
+/**
+ * Offsets panic if they move past what an Instant can represent.
+ */
+impl Measurable for Instant {
+    type Measure = Duration;
+
+    fn measure_to(&self, later: &Self) -> Duration {
+        later.saturating_duration_since(*self)
+    }
+
+    fn offset_forward(&self, by: &Duration) -> Self {
+        *self + *by
+    }
+
+    fn offset_backward(&self, by: &Duration) -> Self {
+        *self - *by
+    }
+
+    fn checked_offset_forward(&self, by: &Duration) -> Option<Self> {
+        self.checked_add(*by)
+    }
+
+    fn checked_offset_backward(&self, by: &Duration) -> Option<Self> {
+        self.checked_sub(*by)
+    }
+
+    fn measure_as_f64(measure: &Duration) -> f64 {
+        measure.as_secs_f64()
+    }
+
+    fn floor_to_grid(&self, origin: &Self, width: &Duration) -> Self {
+        *origin + grid_offset(origin.measure_to(self), width)
+    }
+
+    fn nth_grid_point(&self, width: &Duration, n: u64) -> Option<Self> {
+        self.checked_add(grid_span(width, n)?)
+    }
+}
+
+
+/**
+ * A Duration can be a domain of its own (ex: time since some event). Offsets
+ * saturate at zero and Duration::MAX.
+ */
+impl Measurable for Duration {
+    type Measure = Duration;
+
+    fn measure_to(&self, later: &Self) -> Duration {
+        later.saturating_sub(*self)
+    }
+
+    fn offset_forward(&self, by: &Duration) -> Self {
+        self.saturating_add(*by)
+    }
+
+    fn offset_backward(&self, by: &Duration) -> Self {
+        self.saturating_sub(*by)
+    }
+
+    fn checked_offset_forward(&self, by: &Duration) -> Option<Self> {
+        self.checked_add(*by)
+    }
+
+    fn checked_offset_backward(&self, by: &Duration) -> Option<Self> {
+        self.checked_sub(*by)
+    }
+
+    fn measure_as_f64(measure: &Duration) -> f64 {
+        measure.as_secs_f64()
+    }
+
+    fn floor_to_grid(&self, origin: &Self, width: &Duration) -> Self {
+        *origin + grid_offset(origin.measure_to(self), width)
+    }
+
+    fn nth_grid_point(&self, width: &Duration, n: u64) -> Option<Self> {
+        self.checked_add(grid_span(width, n)?)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+    use crate::measure::Measurable;
+    use crate::span::{Span, SimpleSpan, CreatableSpan};
+    use crate::vecspet::VecSpet;
+    use crate::overlapping::n_overlapping;
+
+    type TimeSpan = SimpleSpan<SystemTime>;
+    type TimeSpet = VecSpet<TimeSpan>;
+
+    fn at(seconds: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    fn minutes(minutes: u64) -> Duration {
+        Duration::from_secs(minutes * 60)
+    }
+
+    #[test]
+    fn starting_at() {
+        let span = TimeSpan::starting_at(at(100), Duration::from_secs(30));
+
+        assert_eq!(span, TimeSpan::new(at(100), at(130)));
+        assert_eq!(span.measure(), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn union() {
+        let a = TimeSpet::from_sorted_iter(vec![
+            TimeSpan::starting_at(at(0), minutes(10)),
+            TimeSpan::starting_at(at(3600), minutes(10)),
+        ]);
+        let b = TimeSpet::from_sorted_iter(vec![
+            TimeSpan::starting_at(at(300), minutes(10)),
+        ]);
+
+        let result = a.union(&b);
+        assert_eq!(result, TimeSpet::from_sorted_iter(vec![
+            TimeSpan::new(at(0), at(900)),
+            TimeSpan::new(at(3600), at(4200)),
+        ]));
+        assert_eq!(result.measure(), minutes(25));
+    }
+
+    #[test]
+    fn intersection() {
+        let a = TimeSpet::from_sorted_iter(vec![
+            TimeSpan::starting_at(at(0), minutes(10)),
+        ]);
+        let b = TimeSpet::from_sorted_iter(vec![
+            TimeSpan::starting_at(at(300), minutes(10)),
+        ]);
+
+        let result = a.intersection(&b);
+        assert_eq!(result, TimeSpet::from_sorted_iter(vec![
+            TimeSpan::new(at(300), at(600)),
+        ]));
+        assert_eq!(result.measure(), minutes(5));
+    }
+
+    #[test]
+    fn n_overlapping_users() {
+        let logged_in = vec![
+            TimeSpet::from_sorted_iter(vec![TimeSpan::new(at(0), at(100))]),
+            TimeSpet::from_sorted_iter(vec![TimeSpan::new(at(50), at(150))]),
+            TimeSpet::from_sorted_iter(vec![TimeSpan::new(at(90), at(200))]),
+        ];
+
+        let result: TimeSpet = n_overlapping(2, &logged_in);
+        assert_eq!(result, TimeSpet::from_sorted_iter(vec![
+            TimeSpan::new(at(50), at(150)),
+        ]));
+        assert_eq!(result.measure(), Duration::from_secs(100));
+
+        let result: TimeSpet = n_overlapping(3, &logged_in);
+        assert_eq!(result.measure(), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn hourly_buckets() {
+        let spet = TimeSpet::from_sorted_iter(vec![
+            TimeSpan::new(at(1800), at(5400)),
+        ]);
+
+        let covered: Vec<_> = spet.bucketize(at(0), minutes(60))
+            .map(|(bucket, covered, _)| (*bucket.start(), covered))
+            .collect();
+        assert_eq!(covered, vec![
+            (at(0), minutes(30)),
+            (at(3600), minutes(30)),
+        ]);
+    }
+
+    #[test]
+    fn nth_grid_point() {
+        assert_eq!(at(5).nth_grid_point(&minutes(60), 2), Some(at(7205)));
+        assert_eq!(Duration::from_secs(1).nth_grid_point(&Duration::MAX, 2),
+                   None);
+    }
+
+    #[test]
+    fn checked_offsets() {
+        assert_eq!(Duration::from_secs(1).checked_offset_backward(&minutes(1)),
+                   None);
+        assert_eq!(at(60).checked_offset_backward(&minutes(1)),
+                   Some(UNIX_EPOCH));
+    }
+
+    #[test]
+    fn floor_to_grid() {
+        assert_eq!(at(7265).floor_to_grid(&at(5), &minutes(60)), at(7205));
+
+        let origin = Instant::now();
+        let later = origin + Duration::from_millis(2500);
+        assert_eq!(later.floor_to_grid(&origin, &Duration::from_secs(1)),
+                   origin + Duration::from_secs(2));
+    }
+
+    #[test]
+    fn duration_domain() {
+        let spet: VecSpet<SimpleSpan<Duration>> =
+            VecSpet::from_sorted_iter(vec![SimpleSpan::new(
+                Duration::from_secs(1), Duration::from_secs(3))]);
+
+        // The dilation saturates at a Duration of zero.
+        assert_eq!(spet.dilate(minutes(1), Duration::ZERO).measure(),
+                   Duration::from_secs(3));
+    }
+}
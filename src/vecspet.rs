@@ -1,12 +1,13 @@
 use std::fmt::Debug;
-use crate::span::{Span, CreatableSpan};
-use crate::points::{enumerate_points, Point::{StartOf, EndOf}};
+use crate::span::{Span, CreatableSpan, AllenRelation};
+use crate::points::{enumerate_points, Point::{StartOf, EndOf}, Sweep};
 use crate::mergeiter::sorted_chain;
+use crate::measure::Measurable;
 
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq)]
 pub struct VecSpet<S: CreatableSpan> {
-    spans: Vec<S>
+    pub(crate) spans: Vec<S>
 }
 
 
@@ -70,32 +71,682 @@ impl<S: CreatableSpan> VecSpet<S> {
         crate::overlapping::n_overlapping(2, vec![self, other])
     }
 
+    /**
+     * Everything in this spet that isn't in `other`.
+     */
+    pub fn difference(&self, other: &VecSpet<S>) -> VecSpet<S> {
+        let mut difference = SpanBuilder::new();
+        let spets = vec![self.spans.iter(), other.spans.iter()];
+        Self::sweep(spets, |position, counts| {
+            difference.cover(position, counts[0] > 0 && counts[1] == 0);
+        });
+
+        difference.finish()
+    }
+
+    /**
+     * Finds what was added and removed to get from this spet to `other`.
+     *
+     * Both halves are found in a single sweep across the two spets. Applying
+     * the result to this spet (with apply()) gives back the same coverage as
+     * `other`, though not always the same spans: empty spans in `other` are
+     * lost and touching ones come back merged.
+     */
+    pub fn diff(&self, other: &VecSpet<S>) -> SpetDiff<S> {
+        let mut added = SpanBuilder::new();
+        let mut removed = SpanBuilder::new();
+        let spets = vec![self.spans.iter(), other.spans.iter()];
+        Self::sweep(spets, |position, counts| {
+            added.cover(position, counts[0] == 0 && counts[1] > 0);
+            removed.cover(position, counts[0] > 0 && counts[1] == 0);
+        });
+
+        SpetDiff {
+            added: added.finish(),
+            removed: removed.finish(),
+        }
+    }
+
+    /**
+     * Applies a diff made by diff(), removing everything in `diff.removed`
+     * and then adding everything in `diff.added`.
+     *
+     * Spans in the result that touch are merged.
+     */
+    pub fn apply(&self, diff: &SpetDiff<S>) -> VecSpet<S> {
+        let mut result = SpanBuilder::new();
+        let spets = vec![
+            self.spans.iter(),
+            diff.removed.spans.iter(),
+            diff.added.spans.iter(),
+        ];
+        Self::sweep(spets, |position, counts| {
+            result.cover(position,
+                         (counts[0] > 0 && counts[1] == 0) || counts[2] > 0);
+        });
+
+        result.finish()
+    }
+
+    // Sweeps across the points of several spets (given as iterators over
+    // their sorted spans) at once. Once every point at a position has been
+    // seen, `visit` is given that position along with how many spans from
+    // each spet cover what comes right after it.
+    pub(crate) fn sweep<T, I>(spets: Vec<I>,
+            mut visit: impl FnMut(&S::Domain, &[isize]))
+            where T: Span<Domain = S::Domain>,
+                  I: Iterator<Item = T> {
+        let mut sweep = Sweep::new(spets);
+        while let Some((position, counts)) = sweep.next_position() {
+            visit(&position, counts);
+        }
+    }
+
     pub fn filter_gaps(&self,
             should_keep: impl Fn(&S::Domain, &S::Domain) -> bool)
             -> VecSpet<S> {
         let mut result: Vec<S> = Vec::new();
-        if self.spans.is_empty() {
-            return VecSpet { spans: result };
+        let last = match self.spans.last() {
+            Some(last) => last,
+            None => return VecSpet { spans: result },
+        };
+
+        let mut pending_start = self.spans[0].start().clone();
+        for gap in self.gaps() {
+            if should_keep(gap.start(), gap.end()) {
+                result.push(S::new(pending_start, gap.start().clone()));
+                pending_start = gap.end().clone();
+            }
+        }
+
+        result.push(S::new(pending_start, last.end().clone()));
+        VecSpet { spans: result }
+    }
+
+    /**
+     * Iterates over the gaps between consecutive spans.
+     *
+     * Ex: the gaps of `{1–2, 4–5, 5–7}` are `2–4` and `5–5`.
+     */
+    pub fn gaps(&self) -> Gaps<'_, S> {
+        Gaps { pairs: self.spans.windows(2) }
+    }
+
+    /**
+     * Iterates over the non-empty gaps within `window`.
+     *
+     * Unlike gaps() this includes the leading gap between the start of
+     * `window` and the first span, and the trailing gap between the last span
+     * and the end of `window`. Gaps are clipped to `window`. Ex: the gaps of
+     * `{1–2, 4–5}` within `0–10` are `0–1`, `2–4`, and `5–10`.
+     */
+    pub fn gaps_within<W: Span<Domain = S::Domain>>(&self, window: &W)
+            -> WindowedGaps<'_, S> {
+        WindowedGaps {
+            spans: self.spans.iter(),
+            cursor: Some(window.start().clone()),
+            window_end: window.end().clone(),
+        }
+    }
+
+    /**
+     * Keeps only the spans that have one of `relations` to `reference`.
+     *
+     * Ex: `filter_by_relation(&b, &[Meets, Overlaps])` keeps the spans `a`
+     * where "a meets b" or "a overlaps b".
+     */
+    pub fn filter_by_relation<R: Span<Domain = S::Domain>>(&self,
+            reference: &R,
+            relations: &[AllenRelation]) -> VecSpet<S> {
+        VecSpet {
+            spans: self.spans.iter()
+                .filter(|span| relations.contains(&span.relation(reference)))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /**
+     * Whether any span contains `point`.
+     *
+     * Found with a binary search, so this is O(log N).
+     */
+    pub fn contains(&self, point: &S::Domain) -> bool {
+        let index = self.spans.partition_point(|span| span.end() <= point);
+        self.spans.get(index).is_some_and(|span| span.contains(point))
+    }
+
+    /**
+     * The spans that overlap `window`, borrowed straight from this spet.
+     *
+     * Found with a binary search, so this is O(log N) and doesn't allocate.
+     * Spans that only touch `window` (ex: `1–2` for the window `2–5`) don't
+     * overlap it.
+     */
+    pub fn slice<W: Span<Domain = S::Domain>>(&self, window: &W) -> &[S] {
+        if window.start() >= window.end() {
+            return &[];
+        }
+
+        // Since the spans are sorted and don't overlap, their ends are
+        // sorted too.
+        let first = self.spans.partition_point(
+            |span| span.end() <= window.start());
+        let last = self.spans.partition_point(
+            |span| span.start() < window.end());
+        &self.spans[first..last]
+    }
+
+    /**
+     * Trims the spet down to the parts within `window`.
+     *
+     * Ex: clipping `{1–3, 4–5, 6–9}` to `2–7` gives `{2–3, 4–5, 6–7}`. This is
+     * O(log N + K) where K is the number of spans in the result.
+     */
+    pub fn clip<W: Span<Domain = S::Domain>>(&self, window: &W)
+            -> VecSpet<S> {
+        VecSpet {
+            spans: self.slice(window).iter().map(|span| S::new(
+                std::cmp::max(span.start(), window.start()).clone(),
+                std::cmp::min(span.end(), window.end()).clone()))
+                .collect(),
         }
+    }
 
-        let mut pending_start = self.spans[0].start();
-        for i in 0..self.spans.len() - 1 {
-            if should_keep(self.spans[i].end(), self.spans[i + 1].start()) {
-                result.push(S::new(pending_start.clone(),
-                            self.spans[i].end().clone()));
-                pending_start = &self.spans[i + 1].start();
+    /**
+     * Splits spans at each of `points`, which must be sorted in ascending
+     * order.
+     *
+     * Ex: splitting `{1–5, 6–9}` at `[2, 4, 8]` gives
+     * `{1–2, 2–4, 4–5, 6–8, 8–9}`. The pieces of a split span touch, and
+     * points that aren't strictly inside a span are ignored.
+     */
+    pub fn split_at<I: IntoIterator<Item = S::Domain>>(&self, points: I)
+            -> VecSpet<S> {
+        let mut points = points.into_iter().peekable();
+        let mut result: Vec<S> = Vec::with_capacity(self.spans.len());
+        for span in self.spans.iter() {
+            let mut start = span.start().clone();
+            while let Some(point) = points.peek() {
+                if point >= span.end() {
+                    break;
+                } else if point > &start {
+                    result.push(S::new(start, point.clone()));
+                    start = point.clone();
+                }
+                points.next();
             }
+
+            result.push(S::new(start, span.end().clone()));
         }
 
-        result.push(
-            S::new(pending_start.clone(),
-                   self.spans[self.spans.len() - 1].end().clone()));
         VecSpet { spans: result }
     }
 
+    /**
+     * Keeps only the spans for which `should_keep` returns true.
+     */
+    pub fn filter_spans(&self, should_keep: impl Fn(&S) -> bool)
+            -> VecSpet<S> {
+        VecSpet {
+            spans: self.spans.iter()
+                .filter(|span| should_keep(span))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /**
+     * Maps every start and end through `f` to build a spet in a different
+     * coordinate system (ex: converting milliseconds to seconds).
+     *
+     * `f` must be monotonic (`a <= b` implies `f(a) <= f(b)`), use
+     * try_map_domain() if that isn't certain. The result is normalized:
+     * spans that overlap or touch after mapping are merged, and empty spans
+     * are dropped (including any that were already empty). Use shift() to
+     * just move spans.
+     */
+    pub fn map_domain<U: CreatableSpan>(&self,
+            f: impl Fn(&S::Domain) -> U::Domain) -> VecSpet<U> {
+        VecSpet::coalesce(self.spans.iter().map(
+            |span| U::new(f(span.start()), f(span.end()))))
+    }
+
+    /**
+     * Like map_domain(), but checks that `f` kept every start and end in
+     * order rather than trusting it to be monotonic.
+     */
+    pub fn try_map_domain<U: CreatableSpan>(&self,
+            f: impl Fn(&S::Domain) -> U::Domain)
+            -> Result<VecSpet<U>, NonMonotonicMapping> {
+        let mut mapped: Vec<U> = Vec::with_capacity(self.spans.len());
+        for (index, span) in self.spans.iter().enumerate() {
+            let span = U::new(f(span.start()), f(span.end()));
+            let in_order = span.start() <= span.end() && match mapped.last() {
+                Some(previous) => previous.end() <= span.start(),
+                None => true,
+            };
+            if !in_order {
+                return Err(NonMonotonicMapping { index });
+            }
+
+            mapped.push(span);
+        }
+
+        Ok(VecSpet::coalesce(mapped))
+    }
+
     pub fn is_empty(&self) -> bool {
         self.spans.is_empty()
     }
+
+    // Builds a spet from spans that are sorted by their starts, merging any
+    // spans that overlap or touch and dropping any that are empty.
+    pub(crate) fn coalesce<I: IntoIterator<Item = S>>(spans: I)
+            -> VecSpet<S> {
+        let mut result: Vec<S> = Vec::new();
+        for span in spans {
+            if span.start() >= span.end() {
+                continue;
+            }
+
+            match result.last_mut() {
+                Some(last) if span.start() <= last.end() => {
+                    if span.end() > last.end() {
+                        *last = S::new(last.start().clone(),
+                                       span.end().clone());
+                    }
+                },
+                _ => result.push(span),
+            }
+        }
+
+        VecSpet { spans: result }
+    }
+}
+
+
+impl<S: CreatableSpan> VecSpet<S> where S::Domain: Measurable {
+    /**
+     * The total measure of all the spans (ex: their total duration).
+     */
+    pub fn measure(&self) -> <S::Domain as Measurable>::Measure {
+        self.spans.iter().fold(
+            <S::Domain as Measurable>::Measure::default(),
+            |total, span| total + span.measure())
+    }
+
+    /**
+     * Merges neighboring spans whose gap is shorter than `min_gap`.
+     *
+     * Ex: bridging gaps shorter than 3 in `{1–2, 4–5, 9–10}` gives
+     * `{1–5, 9–10}`.
+     */
+    pub fn bridge_gaps_shorter_than(&self,
+            min_gap: <S::Domain as Measurable>::Measure) -> VecSpet<S> {
+        self.filter_gaps(|end, start| end.measure_to(start) >= min_gap)
+    }
+
+    /**
+     * Drops the spans shorter than `min_length`.
+     */
+    pub fn remove_spans_shorter_than(&self,
+            min_length: <S::Domain as Measurable>::Measure) -> VecSpet<S> {
+        self.filter_spans(|span| span.measure() >= min_length)
+    }
+
+    /**
+     * Moves every span forward by `offset`.
+     *
+     * Each span maps to exactly one span, so empty and touching spans are
+     * kept as they are. Gives None if any start or end would move past the
+     * edge of the domain (ex: below 0 for unsigned integers), since clamping
+     * it there could collapse spans onto one another.
+     */
+    pub fn shift(&self, offset: <S::Domain as Measurable>::Measure)
+            -> Option<VecSpet<S>> {
+        self.translate(|value| value.checked_offset_forward(&offset))
+    }
+
+    /**
+     * Moves every span backward by `offset`, like shift().
+     */
+    pub fn shift_back(&self, offset: <S::Domain as Measurable>::Measure)
+            -> Option<VecSpet<S>> {
+        self.translate(|value| value.checked_offset_backward(&offset))
+    }
+
+    // Maps every span through the monotonic `f` one-to-one, or gives None if
+    // `f` does for any start or end.
+    fn translate(&self, f: impl Fn(&S::Domain) -> Option<S::Domain>)
+            -> Option<VecSpet<S>> {
+        let spans = self.spans.iter()
+            .map(|span| Some(S::new(f(span.start())?, f(span.end())?)))
+            .collect::<Option<Vec<S>>>()?;
+        Some(VecSpet { spans })
+    }
+
+    /**
+     * Iterates over fixed-width buckets `origin + k * width` to
+     * `origin + (k + 1) * width`.
+     *
+     * Each item is the bucket's span, the measure of the spet within that
+     * bucket, and the spet clipped to the bucket. Buckets that contain
+     * nothing are skipped unless Buckets::include_empty() is used. Anything
+     * before `origin` is ignored.
+     *
+     * Skipping a gap is O(1) for integers, floats and times, since they
+     * override Measurable::floor_to_grid(). Other domains use the default,
+     * which steps one width at a time, so a huge gap takes as long as
+     * including every bucket in it would.
+     */
+    pub fn bucketize(&self,
+            origin: S::Domain,
+            width: <S::Domain as Measurable>::Measure) -> Buckets<'_, S> {
+        assert!(width > <S::Domain as Measurable>::Measure::default());
+        Buckets {
+            spans: &self.spans,
+            next_start: origin.clone(),
+            origin,
+            width,
+            include_empty: false,
+        }
+    }
+
+    /**
+     * Finds the gap with the greatest measure (the earliest one if several
+     * are tied).
+     */
+    pub fn largest_gap(&self) -> Option<S> {
+        let mut largest: Option<(S, <S::Domain as Measurable>::Measure)> =
+            None;
+        for gap in self.gaps() {
+            let measure = gap.measure();
+            match &largest {
+                Some((_, largest_measure)) if measure <= *largest_measure => {},
+                _ => largest = Some((gap, measure)),
+            }
+        }
+
+        largest.map(|(gap, _)| gap)
+    }
+
+    /**
+     * The measure of each gap, in the same order as gaps().
+     */
+    pub fn gap_measures(&self)
+            -> impl Iterator<Item = <S::Domain as Measurable>::Measure> + '_ {
+        self.gaps().map(|gap| gap.measure())
+    }
+
+    /**
+     * The measure of each gap, sorted in ascending order.
+     *
+     * Handy for computing percentiles of gap lengths.
+     */
+    pub fn gap_distribution(&self)
+            -> Vec<<S::Domain as Measurable>::Measure> {
+        let mut measures: Vec<_> = self.gap_measures().collect();
+        measures.sort();
+        measures
+    }
+
+    /**
+     * Grows each span by `before` at its start and `after` at its end.
+     *
+     * Spans that overlap or touch once grown are merged. Ex: dilating
+     * `{1–2, 4–5}` by 1 on both sides gives `{0–6}`.
+     */
+    pub fn dilate(&self,
+            before: <S::Domain as Measurable>::Measure,
+            after: <S::Domain as Measurable>::Measure) -> VecSpet<S> {
+        Self::coalesce(self.spans.iter().map(|span| S::new(
+            span.start().offset_backward(&before),
+            span.end().offset_forward(&after))))
+    }
+
+    /**
+     * Shrinks each span by `before` at its start and `after` at its end.
+     *
+     * Spans that touch are treated as a single span (so no gap opens up
+     * where they meet). A span that shrinks to exactly zero length is kept
+     * (so closing() keeps a lone event as its own session), but one that
+     * would end before it starts is dropped.
+     */
+    pub fn erode(&self,
+            before: <S::Domain as Measurable>::Measure,
+            after: <S::Domain as Measurable>::Measure) -> VecSpet<S> {
+        // Eroded spans stay within the merged ones, which have gaps between
+        // them, so they can't overlap or touch.
+        let merged = Self::coalesce(self.spans.iter().cloned());
+        VecSpet {
+            spans: merged.spans.into_iter()
+                .map(|span| S::new(
+                    span.start().offset_forward(&before),
+                    span.end().offset_backward(&after)))
+                .filter(|span| span.start() <= span.end())
+                .collect(),
+        }
+    }
+
+    /**
+     * Erodes then dilates, removing spans too short to survive the erosion
+     * while leaving the rest as they were.
+     */
+    pub fn opening(&self,
+            before: <S::Domain as Measurable>::Measure,
+            after: <S::Domain as Measurable>::Measure) -> VecSpet<S> {
+        self.erode(before.clone(), after.clone()).dilate(before, after)
+    }
+
+    /**
+     * Dilates then erodes, filling in gaps too short to survive the dilation
+     * while leaving the rest as they were.
+     */
+    pub fn closing(&self,
+            before: <S::Domain as Measurable>::Measure,
+            after: <S::Domain as Measurable>::Measure) -> VecSpet<S> {
+        self.dilate(before.clone(), after.clone()).erode(before, after)
+    }
+}
+
+
+/**
+ * What was added to and removed from one spet to get another.
+ *
+ * Created by VecSpet::diff().
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpetDiff<S: CreatableSpan> {
+    pub added: VecSpet<S>,
+    pub removed: VecSpet<S>,
+}
+
+
+// Collects spans during a sweep. The sweep reports, at each position it stops
+// at, whether what follows should be covered.
+pub(crate) struct SpanBuilder<S: CreatableSpan> {
+    spans: Vec<S>,
+    pending_start: Option<S::Domain>,
+}
+
+
+impl<S: CreatableSpan> SpanBuilder<S> {
+    pub(crate) fn new() -> Self {
+        SpanBuilder { spans: Vec::new(), pending_start: None }
+    }
+
+    pub(crate) fn cover(&mut self, position: &S::Domain, covered: bool) {
+        if covered {
+            if self.pending_start.is_none() {
+                self.pending_start = Some(position.clone());
+            }
+        } else if let Some(start) = self.pending_start.take() {
+            self.spans.push(S::new(start, position.clone()));
+        }
+    }
+
+    pub(crate) fn finish(self) -> VecSpet<S> {
+        assert!(self.pending_start.is_none());
+        VecSpet { spans: self.spans }
+    }
+}
+
+
+/**
+ * Error for VecSpet::try_map_domain() when the mapping put a span's start and
+ * end (or two neighboring spans) out of order.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NonMonotonicMapping {
+    // Index of the first span whose mapped points were out of order.
+    pub index: usize,
+}
+
+
+impl std::fmt::Display for NonMonotonicMapping {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "mapping is not monotonic (at span {})", self.index)
+    }
+}
+
+
+impl std::error::Error for NonMonotonicMapping {}
+
+
+/**
+ * Iterator over the gaps between consecutive spans of a VecSpet.
+ *
+ * Created by VecSpet::gaps().
+ */
+pub struct Gaps<'a, S: CreatableSpan> {
+    pairs: std::slice::Windows<'a, S>,
+}
+
+
+impl<'a, S: CreatableSpan> Iterator for Gaps<'a, S> {
+    type Item = S;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pairs.next().map(
+            |pair| S::new(pair[0].end().clone(), pair[1].start().clone()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.pairs.size_hint()
+    }
+}
+
+
+/**
+ * Iterator over the gaps of a VecSpet within a window.
+ *
+ * Created by VecSpet::gaps_within().
+ */
+pub struct WindowedGaps<'a, S: CreatableSpan> {
+    spans: std::slice::Iter<'a, S>,
+
+    // Everything before the cursor has been accounted for. This is None once
+    // the trailing gap (if any) has been yielded.
+    cursor: Option<S::Domain>,
+
+    window_end: S::Domain,
+}
+
+
+impl<'a, S: CreatableSpan> Iterator for WindowedGaps<'a, S> {
+    type Item = S;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut cursor = self.cursor.take()?;
+
+        for span in self.spans.by_ref() {
+            if span.end() <= &cursor {
+                continue;
+            } else if span.start() >= &self.window_end {
+                break;
+            } else if span.start() > &cursor {
+                self.cursor = Some(span.end().clone());
+                return Some(S::new(cursor, span.start().clone()));
+            }
+
+            cursor = span.end().clone();
+        }
+
+        if cursor < self.window_end {
+            Some(S::new(cursor, self.window_end.clone()))
+        } else {
+            None
+        }
+    }
+}
+
+
+/**
+ * Iterator over the fixed-width buckets of a VecSpet.
+ *
+ * Created by VecSpet::bucketize().
+ */
+pub struct Buckets<'a, S: CreatableSpan> where S::Domain: Measurable {
+    // The spans that haven't been fully covered by a yielded bucket yet.
+    spans: &'a [S],
+
+    origin: S::Domain,
+    width: <S::Domain as Measurable>::Measure,
+    next_start: S::Domain,
+    include_empty: bool,
+}
+
+
+impl<'a, S: CreatableSpan> Buckets<'a, S> where S::Domain: Measurable {
+    /**
+     * Also yields buckets that contain nothing (up until the last bucket
+     * that contains something).
+     */
+    pub fn include_empty(mut self) -> Self {
+        self.include_empty = true;
+        self
+    }
+}
+
+
+impl<'a, S: CreatableSpan> Iterator for Buckets<'a, S>
+        where S::Domain: Measurable {
+    type Item = (S, <S::Domain as Measurable>::Measure, VecSpet<S>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((first, rest)) = self.spans.split_first() {
+            if first.end() > &self.next_start {
+                break;
+            }
+            self.spans = rest;
+        }
+
+        let first = self.spans.first()?;
+        let mut end = self.next_start.offset_forward(&self.width);
+        if !self.include_empty && first.start() >= &end {
+            self.next_start = first.start().floor_to_grid(
+                &self.origin, &self.width);
+            end = self.next_start.offset_forward(&self.width);
+        }
+
+        let start = std::mem::replace(&mut self.next_start, end.clone());
+        let mut covered = <S::Domain as Measurable>::Measure::default();
+        let mut pieces: Vec<S> = Vec::new();
+        for span in self.spans.iter() {
+            if span.start() >= &end {
+                break;
+            }
+
+            let piece = S::new(
+                std::cmp::max(span.start(), &start).clone(),
+                std::cmp::min(span.end(), &end).clone());
+            covered = covered + piece.measure();
+            pieces.push(piece);
+        }
+
+        Some((S::new(start, end), covered, VecSpet { spans: pieces }))
+    }
 }
 
 
@@ -233,6 +884,1050 @@ mod tests {
         }
     }
 
+    mod difference {
+        use crate::vecspet::VecSpet;
+        use crate::span::{SimpleSpan, CreatableSpan};
+
+        type SSpan = SimpleSpan<i32>;
+
+        #[test]
+        fn simple() {
+            let a = VecSpet {
+                spans: vec![SSpan::new(1, 5), SSpan::new(7, 9)],
+            };
+            let b = VecSpet {
+                spans: vec![SSpan::new(3, 8)],
+            };
+
+            assert_eq!(a.difference(&b).spans, vec![
+                SSpan::new(1, 3),
+                SSpan::new(8, 9),
+            ]);
+        }
+
+        #[test]
+        fn punch_holes() {
+            let a = VecSpet { spans: vec![SSpan::new(0, 10)] };
+            let b = VecSpet {
+                spans: vec![SSpan::new(2, 3), SSpan::new(3, 4), SSpan::new(6, 7)],
+            };
+
+            assert_eq!(a.difference(&b).spans, vec![
+                SSpan::new(0, 2),
+                SSpan::new(4, 6),
+                SSpan::new(7, 10),
+            ]);
+        }
+
+        #[test]
+        fn everything_removed() {
+            let a = VecSpet { spans: vec![SSpan::new(2, 4)] };
+            let b = VecSpet { spans: vec![SSpan::new(0, 10)] };
+
+            assert!(a.difference(&b).is_empty());
+        }
+    }
+
+    mod diff {
+        use crate::vecspet::{VecSpet, SpetDiff};
+        use crate::span::{SimpleSpan, CreatableSpan};
+
+        type SSpan = SimpleSpan<i32>;
+
+        #[test]
+        fn added_and_removed() {
+            let before = VecSpet {
+                spans: vec![SSpan::new(1, 5), SSpan::new(8, 10)],
+            };
+            let after = VecSpet {
+                spans: vec![SSpan::new(3, 6), SSpan::new(8, 10)],
+            };
+
+            assert_eq!(before.diff(&after), SpetDiff {
+                added: VecSpet { spans: vec![SSpan::new(5, 6)] },
+                removed: VecSpet { spans: vec![SSpan::new(1, 3)] },
+            });
+        }
+
+        #[test]
+        fn unchanged() {
+            let a = VecSpet {
+                spans: vec![SSpan::new(1, 5), SSpan::new(8, 10)],
+            };
+
+            let diff = a.diff(&a);
+            assert!(diff.added.is_empty());
+            assert!(diff.removed.is_empty());
+        }
+
+        #[test]
+        fn from_empty() {
+            let before = VecSpet::default();
+            let after = VecSpet {
+                spans: vec![SSpan::new(1, 5), SSpan::new(8, 10)],
+            };
+
+            let diff = before.diff(&after);
+            assert_eq!(diff.added, after);
+            assert!(diff.removed.is_empty());
+        }
+
+        #[test]
+        fn apply_round_trips() {
+            let before = VecSpet {
+                spans: vec![
+                    SSpan::new(0, 2),
+                    SSpan::new(4, 9),
+                    SSpan::new(12, 15),
+                ],
+            };
+            let after = VecSpet {
+                spans: vec![
+                    SSpan::new(1, 5),
+                    SSpan::new(6, 7),
+                    SSpan::new(9, 12),
+                    SSpan::new(20, 21),
+                ],
+            };
+
+            assert_eq!(before.apply(&before.diff(&after)), after);
+            assert_eq!(after.apply(&after.diff(&before)), before);
+        }
+
+        #[test]
+        fn apply_merges_touching() {
+            let before = VecSpet { spans: vec![SSpan::new(1, 3)] };
+            let after = VecSpet { spans: vec![SSpan::new(1, 5)] };
+
+            let diff = before.diff(&after);
+            assert_eq!(diff.added.spans, vec![SSpan::new(3, 5)]);
+            assert_eq!(before.apply(&diff), after);
+        }
+
+        #[test]
+        fn apply_gives_same_coverage() {
+            let before = VecSpet { spans: vec![SSpan::new(0, 1)] };
+            let after = VecSpet {
+                spans: vec![
+                    SSpan::new(2, 2),
+                    SSpan::new(3, 5),
+                    SSpan::new(5, 6),
+                ],
+            };
+
+            let applied = before.apply(&before.diff(&after));
+            assert_eq!(applied.spans, vec![SSpan::new(3, 6)]);
+            assert_eq!(applied, VecSpet::coalesce(after.spans));
+        }
+    }
+
+    mod filter_by_relation {
+        use crate::vecspet::VecSpet;
+        use crate::span::{SimpleSpan, CreatableSpan, AllenRelation::*};
+        use crate::overlapping::n_overlapping;
+
+        type SSpan = SimpleSpan<i32>;
+
+        #[test]
+        fn single_relation() {
+            let a = VecSpet {
+                spans: vec![
+                    SSpan::new(1, 3),
+                    SSpan::new(4, 6),
+                    SSpan::new(7, 9),
+                ],
+            };
+
+            let result = a.filter_by_relation(&SSpan::new(2, 8), &[Overlaps]);
+            assert_eq!(result.spans, vec![SSpan::new(1, 3)]);
+        }
+
+        #[test]
+        fn several_relations() {
+            let a = VecSpet {
+                spans: vec![
+                    SSpan::new(1, 3),
+                    SSpan::new(4, 6),
+                    SSpan::new(7, 9),
+                    SSpan::new(10, 11),
+                ],
+            };
+
+            let result = a.filter_by_relation(
+                &SSpan::new(2, 8),
+                &[OverlappedBy, During, Overlaps]);
+            assert_eq!(result.spans, vec![
+                SSpan::new(1, 3),
+                SSpan::new(4, 6),
+                SSpan::new(7, 9),
+            ]);
+        }
+
+        #[test]
+        fn no_relations() {
+            let a = VecSpet { spans: vec![SSpan::new(1, 3)] };
+
+            let result = a.filter_by_relation(&SSpan::new(1, 3), &[]);
+            assert!(result.is_empty());
+        }
+
+        #[test]
+        fn zero_length_from_n_overlapping() {
+            // n_overlapping leaves zero-length spans behind when one of its
+            // inputs has a zero-length span.
+            let overlap: VecSpet<SSpan> = n_overlapping(2, vec![
+                vec![SSpan::new(1, 6)],
+                vec![SSpan::new(2, 2), SSpan::new(3, 5)],
+            ]);
+            assert_eq!(overlap.spans, vec![
+                SSpan::new(2, 2),
+                SSpan::new(3, 5),
+            ]);
+
+            let reference = SSpan::new(2, 5);
+            assert_eq!(
+                overlap.filter_by_relation(&reference, &[Meets]).spans,
+                vec![SSpan::new(2, 2)]);
+            assert_eq!(
+                overlap.filter_by_relation(&reference, &[Finishes]).spans,
+                vec![SSpan::new(3, 5)]);
+        }
+    }
+
+    mod dilate {
+        use crate::vecspet::VecSpet;
+        use crate::span::{SimpleSpan, CreatableSpan};
+
+        type SSpan = SimpleSpan<i32>;
+
+        #[test]
+        fn pads_each_span() {
+            let a = VecSpet {
+                spans: vec![SSpan::new(1, 2), SSpan::new(10, 12)],
+            };
+
+            assert_eq!(a.dilate(1, 2).spans, vec![
+                SSpan::new(0, 4),
+                SSpan::new(9, 14),
+            ]);
+        }
+
+        #[test]
+        fn merges_overlapping() {
+            let a = VecSpet {
+                spans: vec![SSpan::new(1, 2), SSpan::new(4, 5)],
+            };
+
+            assert_eq!(a.dilate(2, 2).spans, vec![SSpan::new(-1, 7)]);
+        }
+
+        #[test]
+        fn merges_touching() {
+            let a = VecSpet {
+                spans: vec![SSpan::new(1, 2), SSpan::new(4, 5)],
+            };
+
+            assert_eq!(a.dilate(1, 1).spans, vec![SSpan::new(0, 6)]);
+        }
+
+        #[test]
+        fn zero_length() {
+            let a = VecSpet { spans: vec![SSpan::new(3, 3)] };
+
+            assert_eq!(a.dilate(1, 1).spans, vec![SSpan::new(2, 4)]);
+        }
+
+        #[test]
+        fn saturates_unsigned() {
+            let a = VecSpet { spans: vec![SimpleSpan::new(1u32, 2)] };
+
+            assert_eq!(a.dilate(5, 0).spans, vec![SimpleSpan::new(0, 2)]);
+        }
+    }
+
+    mod erode {
+        use crate::vecspet::VecSpet;
+        use crate::span::{SimpleSpan, CreatableSpan};
+
+        type SSpan = SimpleSpan<i32>;
+
+        #[test]
+        fn shrinks_each_span() {
+            let a = VecSpet {
+                spans: vec![SSpan::new(1, 5), SSpan::new(10, 20)],
+            };
+
+            assert_eq!(a.erode(1, 2).spans, vec![
+                SSpan::new(2, 3),
+                SSpan::new(11, 18),
+            ]);
+        }
+
+        #[test]
+        fn drops_vanishing_spans() {
+            let a = VecSpet {
+                spans: vec![SSpan::new(1, 2), SSpan::new(10, 20)],
+            };
+
+            assert_eq!(a.erode(1, 1).spans, vec![SSpan::new(11, 19)]);
+        }
+
+        #[test]
+        fn keeps_zero_length_spans() {
+            let a = VecSpet {
+                spans: vec![SSpan::new(1, 3), SSpan::new(10, 20)],
+            };
+
+            assert_eq!(a.erode(1, 1).spans, vec![
+                SSpan::new(2, 2),
+                SSpan::new(11, 19),
+            ]);
+        }
+
+        #[test]
+        fn touching_spans_erode_together() {
+            let a = VecSpet {
+                spans: vec![SSpan::new(1, 3), SSpan::new(3, 5)],
+            };
+
+            assert_eq!(a.erode(1, 1).spans, vec![SSpan::new(2, 4)]);
+        }
+    }
+
+    mod opening {
+        use crate::vecspet::VecSpet;
+        use crate::span::{SimpleSpan, CreatableSpan};
+
+        type SSpan = SimpleSpan<i32>;
+
+        #[test]
+        fn removes_short_spans() {
+            let a = VecSpet {
+                spans: vec![SSpan::new(1, 2), SSpan::new(10, 20)],
+            };
+
+            assert_eq!(a.opening(1, 1).spans, vec![SSpan::new(10, 20)]);
+        }
+    }
+
+    mod closing {
+        use crate::vecspet::VecSpet;
+        use crate::span::{SimpleSpan, CreatableSpan};
+
+        type SSpan = SimpleSpan<i32>;
+
+        #[test]
+        fn fills_short_gaps() {
+            let a = VecSpet {
+                spans: vec![
+                    SSpan::new(0, 10),
+                    SSpan::new(12, 20),
+                    SSpan::new(30, 40),
+                ],
+            };
+
+            assert_eq!(a.closing(1, 1).spans, vec![
+                SSpan::new(0, 20),
+                SSpan::new(30, 40),
+            ]);
+        }
+
+        #[test]
+        fn sessionizes() {
+            // Pad each event by 30 on either side, merge, then shrink back.
+            let events = VecSpet {
+                spans: vec![
+                    SSpan::new(0, 0),
+                    SSpan::new(40, 40),
+                    SSpan::new(200, 200),
+                ],
+            };
+
+            assert_eq!(events.closing(30, 30).spans, vec![
+                SSpan::new(0, 40),
+                SSpan::new(200, 200),
+            ]);
+        }
+    }
+
+    mod gaps {
+        use crate::vecspet::VecSpet;
+        use crate::span::{SimpleSpan, CreatableSpan};
+
+        type SSpan = SimpleSpan<i32>;
+
+        #[test]
+        fn between_spans() {
+            let a = VecSpet {
+                spans: vec![
+                    SSpan::new(1, 2),
+                    SSpan::new(4, 5),
+                    SSpan::new(5, 7),
+                ],
+            };
+
+            assert_eq!(a.gaps().collect::<Vec<_>>(), vec![
+                SSpan::new(2, 4),
+                SSpan::new(5, 5),
+            ]);
+        }
+
+        #[test]
+        fn single_span() {
+            let a = VecSpet { spans: vec![SSpan::new(1, 2)] };
+
+            assert_eq!(a.gaps().count(), 0);
+        }
+
+        #[test]
+        fn empty() {
+            let a: VecSpet<SSpan> = VecSpet::default();
+
+            assert_eq!(a.gaps().count(), 0);
+        }
+    }
+
+    mod gaps_within {
+        use crate::vecspet::VecSpet;
+        use crate::span::{SimpleSpan, CreatableSpan};
+
+        type SSpan = SimpleSpan<i32>;
+
+        #[test]
+        fn leading_and_trailing() {
+            let a = VecSpet {
+                spans: vec![SSpan::new(1, 2), SSpan::new(4, 5)],
+            };
+
+            assert_eq!(
+                a.gaps_within(&SSpan::new(0, 10)).collect::<Vec<_>>(),
+                vec![
+                    SSpan::new(0, 1),
+                    SSpan::new(2, 4),
+                    SSpan::new(5, 10),
+                ]);
+        }
+
+        #[test]
+        fn clipped_to_window() {
+            let a = VecSpet {
+                spans: vec![
+                    SSpan::new(1, 2),
+                    SSpan::new(4, 5),
+                    SSpan::new(8, 9),
+                ],
+            };
+
+            assert_eq!(
+                a.gaps_within(&SSpan::new(3, 6)).collect::<Vec<_>>(),
+                vec![SSpan::new(3, 4), SSpan::new(5, 6)]);
+        }
+
+        #[test]
+        fn window_inside_span() {
+            let a = VecSpet { spans: vec![SSpan::new(1, 10)] };
+
+            assert_eq!(a.gaps_within(&SSpan::new(3, 6)).count(), 0);
+        }
+
+        #[test]
+        fn window_aligned_with_spans() {
+            let a = VecSpet {
+                spans: vec![SSpan::new(1, 2), SSpan::new(2, 3)],
+            };
+
+            assert_eq!(a.gaps_within(&SSpan::new(1, 3)).count(), 0);
+        }
+
+        #[test]
+        fn empty_spet() {
+            let a: VecSpet<SSpan> = VecSpet::default();
+
+            assert_eq!(
+                a.gaps_within(&SSpan::new(3, 6)).collect::<Vec<_>>(),
+                vec![SSpan::new(3, 6)]);
+        }
+    }
+
+    mod gap_statistics {
+        use crate::vecspet::VecSpet;
+        use crate::span::{SimpleSpan, CreatableSpan};
+
+        type SSpan = SimpleSpan<i32>;
+
+        fn example() -> VecSpet<SSpan> {
+            VecSpet {
+                spans: vec![
+                    SSpan::new(0, 1),
+                    SSpan::new(5, 6),
+                    SSpan::new(7, 8),
+                    SSpan::new(12, 13),
+                ],
+            }
+        }
+
+        #[test]
+        fn largest_gap() {
+            assert_eq!(example().largest_gap(), Some(SSpan::new(1, 5)));
+        }
+
+        #[test]
+        fn largest_gap_of_single_span() {
+            let a = VecSpet { spans: vec![SSpan::new(0, 1)] };
+
+            assert_eq!(a.largest_gap(), None);
+        }
+
+        #[test]
+        fn gap_measures() {
+            assert_eq!(example().gap_measures().collect::<Vec<_>>(),
+                       vec![4, 1, 4]);
+        }
+
+        #[test]
+        fn gap_distribution() {
+            assert_eq!(example().gap_distribution(), vec![1, 4, 4]);
+        }
+    }
+
+    mod filter_spans {
+        use crate::vecspet::VecSpet;
+        use crate::span::{Span, SimpleSpan, CreatableSpan};
+
+        type SSpan = SimpleSpan<i32>;
+
+        #[test]
+        fn simple() {
+            let a = VecSpet {
+                spans: vec![
+                    SSpan::new(1, 2),
+                    SSpan::new(4, 5),
+                    SSpan::new(7, 8),
+                ],
+            };
+
+            assert_eq!(a.filter_spans(|span| *span.start() != 4).spans, vec![
+                SSpan::new(1, 2),
+                SSpan::new(7, 8),
+            ]);
+        }
+    }
+
+    mod measure {
+        use crate::vecspet::VecSpet;
+        use crate::span::{SimpleSpan, CreatableSpan};
+
+        type SSpan = SimpleSpan<i32>;
+
+        #[test]
+        fn sums_spans() {
+            let a = VecSpet {
+                spans: vec![SSpan::new(1, 2), SSpan::new(4, 7)],
+            };
+
+            assert_eq!(a.measure(), 4);
+        }
+
+        #[test]
+        fn empty() {
+            let a: VecSpet<SSpan> = VecSpet::default();
+
+            assert_eq!(a.measure(), 0);
+        }
+    }
+
+    mod duration_thresholds {
+        use crate::vecspet::VecSpet;
+        use crate::span::{SimpleSpan, CreatableSpan};
+
+        type SSpan = SimpleSpan<i32>;
+
+        #[test]
+        fn bridge_gaps_shorter_than() {
+            let a = VecSpet {
+                spans: vec![
+                    SSpan::new(1, 2),
+                    SSpan::new(4, 5),
+                    SSpan::new(8, 10),
+                ],
+            };
+
+            assert_eq!(a.bridge_gaps_shorter_than(3).spans, vec![
+                SSpan::new(1, 5),
+                SSpan::new(8, 10),
+            ]);
+        }
+
+        #[test]
+        fn remove_spans_shorter_than() {
+            let a = VecSpet {
+                spans: vec![
+                    SSpan::new(1, 2),
+                    SSpan::new(4, 6),
+                    SSpan::new(8, 8),
+                ],
+            };
+
+            assert_eq!(a.remove_spans_shorter_than(2).spans, vec![
+                SSpan::new(4, 6),
+            ]);
+        }
+
+        #[test]
+        fn cleaning_pipeline() {
+            // Flickering presence: bridge the short dropouts, then throw away
+            // the blips that are still too short to matter.
+            let a = VecSpet {
+                spans: vec![
+                    SSpan::new(0, 10),
+                    SSpan::new(12, 20),
+                    SSpan::new(50, 51),
+                    SSpan::new(100, 130),
+                ],
+            };
+
+            let cleaned = a
+                .bridge_gaps_shorter_than(5)
+                .remove_spans_shorter_than(10);
+            assert_eq!(cleaned.spans, vec![
+                SSpan::new(0, 20),
+                SSpan::new(100, 130),
+            ]);
+        }
+    }
+
+    mod shift {
+        use crate::vecspet::VecSpet;
+        use crate::span::{SimpleSpan, CreatableSpan};
+
+        type SSpan = SimpleSpan<i32>;
+
+        #[test]
+        fn forward() {
+            let a = VecSpet {
+                spans: vec![SSpan::new(1, 2), SSpan::new(4, 5)],
+            };
+
+            assert_eq!(a.shift(10).unwrap().spans, vec![
+                SSpan::new(11, 12),
+                SSpan::new(14, 15),
+            ]);
+        }
+
+        #[test]
+        fn backward() {
+            let a = VecSpet {
+                spans: vec![SSpan::new(1, 2), SSpan::new(4, 5)],
+            };
+
+            assert_eq!(a.shift_back(3).unwrap().spans, vec![
+                SSpan::new(-2, -1),
+                SSpan::new(1, 2),
+            ]);
+        }
+
+        #[test]
+        fn negative_offset() {
+            let a = VecSpet { spans: vec![SSpan::new(1, 2)] };
+
+            assert_eq!(a.shift(-3), a.shift_back(3));
+        }
+
+        #[test]
+        fn keeps_empty_and_touching_spans() {
+            let a = VecSpet {
+                spans: vec![
+                    SSpan::new(2, 2),
+                    SSpan::new(3, 5),
+                    SSpan::new(5, 6),
+                ],
+            };
+
+            assert_eq!(a.shift(0), Some(a.clone()));
+            assert_eq!(a.shift(1).unwrap().spans, vec![
+                SSpan::new(3, 3),
+                SSpan::new(4, 6),
+                SSpan::new(6, 7),
+            ]);
+        }
+
+        #[test]
+        fn past_unsigned_bounds() {
+            let a = VecSpet {
+                spans: vec![
+                    SimpleSpan::new(0u32, 2),
+                    SimpleSpan::new(3, 5),
+                ],
+            };
+
+            assert_eq!(a.shift_back(10), None);
+            assert_eq!(a.shift(u32::MAX - 5).unwrap().spans, vec![
+                SimpleSpan::new(u32::MAX - 5, u32::MAX - 3),
+                SimpleSpan::new(u32::MAX - 2, u32::MAX),
+            ]);
+            assert_eq!(a.shift(u32::MAX - 4), None);
+        }
+
+        #[test]
+        fn past_signed_bounds() {
+            let a = VecSpet {
+                spans: vec![
+                    SimpleSpan::new(i64::MIN, 0),
+                    SimpleSpan::new(1, i64::MAX),
+                ],
+            };
+
+            assert_eq!(a.shift(1), None);
+            assert_eq!(a.shift_back(1), None);
+            assert_eq!(a.shift(-1), None);
+            assert_eq!(a.shift(0), Some(a.clone()));
+        }
+    }
+
+    mod map_domain {
+        use crate::vecspet::{VecSpet, NonMonotonicMapping};
+        use crate::span::{SimpleSpan, CreatableSpan};
+
+        type SSpan = SimpleSpan<i64>;
+
+        #[test]
+        fn scale_up() {
+            let seconds = VecSpet {
+                spans: vec![SSpan::new(1, 2), SSpan::new(4, 5)],
+            };
+
+            let millis: VecSpet<SSpan> = seconds.map_domain(|s| s * 1000);
+            assert_eq!(millis.spans, vec![
+                SSpan::new(1000, 2000),
+                SSpan::new(4000, 5000),
+            ]);
+        }
+
+        #[test]
+        fn change_span_type() {
+            let a = VecSpet { spans: vec![SSpan::new(1, 2)] };
+
+            let b: VecSpet<SimpleSpan<u8>> = a.map_domain(|v| *v as u8);
+            assert_eq!(b.spans, vec![SimpleSpan::new(1, 2)]);
+        }
+
+        #[test]
+        fn collapsing_spans_merge() {
+            let millis = VecSpet {
+                spans: vec![
+                    SSpan::new(1000, 1500),
+                    SSpan::new(1800, 2600),
+                    SSpan::new(4100, 4200),
+                    SSpan::new(5000, 6000),
+                ],
+            };
+
+            let seconds: VecSpet<SSpan> = millis.map_domain(|ms| ms / 1000);
+            assert_eq!(seconds.spans, vec![
+                SSpan::new(1, 2),
+                SSpan::new(5, 6),
+            ]);
+        }
+
+        #[test]
+        fn checked() {
+            let a = VecSpet {
+                spans: vec![SSpan::new(1000, 1500), SSpan::new(1800, 2600)],
+            };
+
+            let result: Result<VecSpet<SSpan>, _> =
+                a.try_map_domain(|ms| ms / 1000);
+            assert_eq!(result, Ok(VecSpet { spans: vec![SSpan::new(1, 2)] }));
+        }
+
+        #[test]
+        fn checked_rejects_reversal() {
+            let a = VecSpet {
+                spans: vec![SSpan::new(1, 2), SSpan::new(4, 5)],
+            };
+
+            let result: Result<VecSpet<SSpan>, _> =
+                a.try_map_domain(|v| -v);
+            assert_eq!(result, Err(NonMonotonicMapping { index: 0 }));
+        }
+
+        #[test]
+        fn checked_rejects_reordered_spans() {
+            let a = VecSpet {
+                spans: vec![
+                    SSpan::new(1, 2),
+                    SSpan::new(4, 5),
+                    SSpan::new(7, 8),
+                ],
+            };
+
+            let result: Result<VecSpet<SSpan>, _> =
+                a.try_map_domain(|v| if *v == 7 { 0 } else { *v });
+            assert_eq!(result, Err(NonMonotonicMapping { index: 2 }));
+        }
+    }
+
+    mod slice {
+        use crate::vecspet::VecSpet;
+        use crate::span::{SimpleSpan, CreatableSpan};
+
+        type SSpan = SimpleSpan<i32>;
+
+        fn example() -> VecSpet<SSpan> {
+            VecSpet {
+                spans: vec![
+                    SSpan::new(1, 3),
+                    SSpan::new(4, 5),
+                    SSpan::new(6, 9),
+                    SSpan::new(10, 12),
+                ],
+            }
+        }
+
+        #[test]
+        fn overlapping_spans() {
+            let a = example();
+
+            assert_eq!(a.slice(&SSpan::new(2, 7)), &a.spans[0..3]);
+        }
+
+        #[test]
+        fn touching_spans_excluded() {
+            let a = example();
+
+            assert_eq!(a.slice(&SSpan::new(3, 6)), &a.spans[1..2]);
+        }
+
+        #[test]
+        fn window_in_gap() {
+            let a = example();
+
+            assert!(a.slice(&SSpan::new(9, 10)).is_empty());
+        }
+
+        #[test]
+        fn window_outside() {
+            let a = example();
+
+            assert!(a.slice(&SSpan::new(-5, 0)).is_empty());
+            assert!(a.slice(&SSpan::new(12, 20)).is_empty());
+        }
+
+        #[test]
+        fn empty_window() {
+            let a = example();
+
+            assert!(a.slice(&SSpan::new(2, 2)).is_empty());
+        }
+
+        #[test]
+        fn everything() {
+            let a = example();
+
+            assert_eq!(a.slice(&SSpan::new(0, 100)), &a.spans[..]);
+        }
+    }
+
+    mod contains {
+        use crate::vecspet::VecSpet;
+        use crate::span::{SimpleSpan, CreatableSpan};
+
+        type SSpan = SimpleSpan<i32>;
+
+        #[test]
+        fn points() {
+            let a = VecSpet {
+                spans: vec![
+                    SSpan::new(1, 3),
+                    SSpan::new(3, 5),
+                    SSpan::new(7, 7),
+                    SSpan::new(8, 9),
+                ],
+            };
+
+            let contained: Vec<i32> = (0..10).filter(|p| a.contains(p))
+                .collect();
+            assert_eq!(contained, vec![1, 2, 3, 4, 8]);
+        }
+
+        #[test]
+        fn empty() {
+            assert!(!VecSpet::<SSpan>::default().contains(&0));
+        }
+    }
+
+    mod clip {
+        use crate::vecspet::VecSpet;
+        use crate::span::{SimpleSpan, CreatableSpan};
+
+        type SSpan = SimpleSpan<i32>;
+
+        #[test]
+        fn trims_edges() {
+            let a = VecSpet {
+                spans: vec![
+                    SSpan::new(1, 3),
+                    SSpan::new(4, 5),
+                    SSpan::new(6, 9),
+                ],
+            };
+
+            assert_eq!(a.clip(&SSpan::new(2, 7)).spans, vec![
+                SSpan::new(2, 3),
+                SSpan::new(4, 5),
+                SSpan::new(6, 7),
+            ]);
+        }
+
+        #[test]
+        fn window_inside_span() {
+            let a = VecSpet { spans: vec![SSpan::new(1, 10)] };
+
+            assert_eq!(a.clip(&SSpan::new(3, 4)).spans, vec![
+                SSpan::new(3, 4),
+            ]);
+        }
+
+        #[test]
+        fn nothing_in_window() {
+            let a = VecSpet { spans: vec![SSpan::new(1, 2)] };
+
+            assert!(a.clip(&SSpan::new(2, 4)).is_empty());
+        }
+    }
+
+    mod split_at {
+        use crate::vecspet::VecSpet;
+        use crate::span::{SimpleSpan, CreatableSpan};
+
+        type SSpan = SimpleSpan<i32>;
+
+        #[test]
+        fn simple() {
+            let a = VecSpet {
+                spans: vec![SSpan::new(1, 5), SSpan::new(6, 9)],
+            };
+
+            assert_eq!(a.split_at(vec![2, 4, 8]).spans, vec![
+                SSpan::new(1, 2),
+                SSpan::new(2, 4),
+                SSpan::new(4, 5),
+                SSpan::new(6, 8),
+                SSpan::new(8, 9),
+            ]);
+        }
+
+        #[test]
+        fn points_outside_spans() {
+            let a = VecSpet {
+                spans: vec![SSpan::new(1, 5), SSpan::new(6, 9)],
+            };
+
+            assert_eq!(a.split_at(vec![0, 1, 5, 6, 9, 10]), a);
+        }
+
+        #[test]
+        fn many_points_in_one_span() {
+            let a = VecSpet { spans: vec![SSpan::new(0, 4)] };
+
+            assert_eq!(a.split_at(1..4).spans, vec![
+                SSpan::new(0, 1),
+                SSpan::new(1, 2),
+                SSpan::new(2, 3),
+                SSpan::new(3, 4),
+            ]);
+        }
+    }
+
+    mod bucketize {
+        use crate::vecspet::VecSpet;
+        use crate::span::{SimpleSpan, CreatableSpan};
+
+        type SSpan = SimpleSpan<i32>;
+
+        #[test]
+        fn span_crossing_buckets() {
+            let a = VecSpet { spans: vec![SSpan::new(5, 25)] };
+
+            let buckets: Vec<_> = a.bucketize(0, 10).collect();
+            assert_eq!(buckets, vec![
+                (SSpan::new(0, 10), 5,
+                 VecSpet { spans: vec![SSpan::new(5, 10)] }),
+                (SSpan::new(10, 20), 10,
+                 VecSpet { spans: vec![SSpan::new(10, 20)] }),
+                (SSpan::new(20, 30), 5,
+                 VecSpet { spans: vec![SSpan::new(20, 25)] }),
+            ]);
+        }
+
+        #[test]
+        fn several_spans_per_bucket() {
+            let a = VecSpet {
+                spans: vec![SSpan::new(1, 2), SSpan::new(4, 7)],
+            };
+
+            let buckets: Vec<_> = a.bucketize(0, 5).collect();
+            assert_eq!(buckets, vec![
+                (SSpan::new(0, 5), 2,
+                 VecSpet { spans: vec![SSpan::new(1, 2), SSpan::new(4, 5)] }),
+                (SSpan::new(5, 10), 2,
+                 VecSpet { spans: vec![SSpan::new(5, 7)] }),
+            ]);
+        }
+
+        #[test]
+        fn skips_empty_buckets() {
+            let a = VecSpet {
+                spans: vec![SSpan::new(1, 2), SSpan::new(1_000_001, 1_000_002)],
+            };
+
+            let buckets: Vec<_> = a.bucketize(0, 10)
+                .map(|(bucket, covered, _)| (bucket, covered))
+                .collect();
+            assert_eq!(buckets, vec![
+                (SSpan::new(0, 10), 1),
+                (SSpan::new(1_000_000, 1_000_010), 1),
+            ]);
+        }
+
+        #[test]
+        fn include_empty() {
+            let a = VecSpet {
+                spans: vec![SSpan::new(12, 14), SSpan::new(31, 32)],
+            };
+
+            let buckets: Vec<_> = a.bucketize(0, 10)
+                .include_empty()
+                .map(|(bucket, covered, _)| (bucket, covered))
+                .collect();
+            assert_eq!(buckets, vec![
+                (SSpan::new(0, 10), 0),
+                (SSpan::new(10, 20), 2),
+                (SSpan::new(20, 30), 0),
+                (SSpan::new(30, 40), 1),
+            ]);
+        }
+
+        #[test]
+        fn ignores_before_origin() {
+            let a = VecSpet {
+                spans: vec![SSpan::new(-10, -5), SSpan::new(-2, 3)],
+            };
+
+            let buckets: Vec<_> = a.bucketize(0, 10)
+                .map(|(bucket, covered, _)| (bucket, covered))
+                .collect();
+            assert_eq!(buckets, vec![(SSpan::new(0, 10), 3)]);
+        }
+
+        #[test]
+        fn empty() {
+            let a: VecSpet<SSpan> = VecSpet::default();
+
+            assert_eq!(a.bucketize(0, 10).include_empty().count(), 0);
+        }
+    }
+
     mod filter_gaps {
         use crate::vecspet::VecSpet;
         use crate::span::{SimpleSpan, CreatableSpan};
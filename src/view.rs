@@ -0,0 +1,605 @@
+/*!
+ * A fixed-width file format for spets over integers, which can be queried in
+ * place (ex: straight out of a memory-mapped file) through a SpetView.
+ *
+ * The layout is a 16 byte header followed by each span's start and end as
+ * little-endian integers:
+ *
+ * ```text
+ * offset  size   field
+ * 0       4      magic b"SPFW"
+ * 4       1      version (currently 1)
+ * 5       1      1 if the domain is signed, otherwise 0
+ * 6       1      width of the domain in bytes (1, 2, 4, or 8)
+ * 7       1      reserved, always 0
+ * 8       8      number of spans, as a little-endian u64
+ * 16      ...    start and end of each span, in order
+ * ```
+ *
+ * Since the header is 16 bytes, every start and end is aligned to its width
+ * as long as the file itself is (which memory maps always are).
+ */
+
+use std::fmt::{self, Debug, Display};
+use std::io::{self, Write};
+use std::marker::PhantomData;
+use crate::span::{Span, SimpleSpan, CreatableSpan};
+use crate::vecspet::{VecSpet, SpanBuilder};
+use crate::mergeiter::sorted_chain;
+
+
+pub const MAGIC: [u8; 4] = *b"SPFW";
+pub const VERSION: u8 = 1;
+pub const HEADER_LEN: usize = 16;
+
+
+/**
+ * An integer type that can be the domain of a SpetView.
+ */
+pub trait FixedWidth: Ord + Copy {
+    const SIGNED: bool;
+    const WIDTH: usize;
+
+    // `bytes` is exactly WIDTH bytes long.
+    fn from_le_slice(bytes: &[u8]) -> Self;
+    fn write_le<W: Write>(self, writer: &mut W) -> io::Result<()>;
+}
+
+
+macro_rules! impl_fixed_width {
+    ($signed:expr, $($t:ty),*) => {
+        $(
+            impl FixedWidth for $t {
+                const SIGNED: bool = $signed;
+                const WIDTH: usize = std::mem::size_of::<$t>();
+
+                fn from_le_slice(bytes: &[u8]) -> Self {
+                    let mut array = [0; std::mem::size_of::<$t>()];
+                    array.copy_from_slice(bytes);
+                    <$t>::from_le_bytes(array)
+                }
+
+                fn write_le<W: Write>(self, writer: &mut W)
+                        -> io::Result<()> {
+                    writer.write_all(&self.to_le_bytes())
+                }
+            }
+        )*
+    };
+}
+
+impl_fixed_width!(false, u8, u16, u32, u64);
+impl_fixed_width!(true, i8, i16, i32, i64);
+
+
+/**
+ * Why bytes couldn't be viewed as a spet.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ViewError {
+    // There were fewer bytes than the header says there should be.
+    Truncated,
+
+    // There were more bytes than the header says there should be.
+    TrailingBytes,
+
+    // The bytes don't start with MAGIC.
+    BadMagic,
+
+    // The bytes were written by a version of the format this can't read.
+    UnsupportedVersion(u8),
+
+    // The header's domain isn't the one being viewed as.
+    WrongDomain { signed: bool, width: u8 },
+
+    // The span (given by index) ends before it starts.
+    Reversed { index: usize },
+
+    // The span (given by index) starts before the previous span ends.
+    Unsorted { index: usize },
+}
+
+
+impl Display for ViewError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ViewError::Truncated => write!(f, "input ended unexpectedly"),
+            ViewError::TrailingBytes => write!(f, "unexpected trailing bytes"),
+            ViewError::BadMagic => write!(f, "not a fixed-width spet"),
+            ViewError::UnsupportedVersion(version) =>
+                write!(f, "unsupported version {}", version),
+            ViewError::WrongDomain { signed, width } =>
+                write!(f, "spet is over {}{}",
+                       if *signed { "i" } else { "u" },
+                       u32::from(*width) * 8),
+            ViewError::Reversed { index } =>
+                write!(f, "span {} ends before it starts", index),
+            ViewError::Unsorted { index } =>
+                write!(f, "span {} overlaps the span before it", index),
+        }
+    }
+}
+
+
+impl std::error::Error for ViewError {}
+
+
+impl<S: CreatableSpan> VecSpet<S> where S::Domain: FixedWidth {
+    /**
+     * Writes the spet in the fixed-width format, to be read back with
+     * SpetView::new().
+     */
+    pub fn write_fixed_width_to<W: Write>(&self, mut writer: W)
+            -> io::Result<()> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&[
+            VERSION,
+            S::Domain::SIGNED as u8,
+            S::Domain::WIDTH as u8,
+            0,
+        ])?;
+        writer.write_all(&(self.spans.len() as u64).to_le_bytes())?;
+
+        for span in &self.spans {
+            span.start().write_le(&mut writer)?;
+            span.end().write_le(&mut writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+
+/**
+ * A spet borrowed from bytes in the fixed-width format, without decoding it.
+ *
+ * Queries binary search the bytes directly, and set operations stream over
+ * them, so only their results are allocated. Creating a view checks the whole
+ * file once (without allocating), so that queries can trust it.
+ */
+#[derive(Clone, Copy)]
+pub struct SpetView<'a, T: FixedWidth> {
+    // Just the spans, without the header.
+    spans: &'a [u8],
+    domain: PhantomData<T>,
+}
+
+
+impl<'a, T: FixedWidth> SpetView<'a, T> {
+    pub fn new(bytes: &'a [u8]) -> Result<Self, ViewError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(if bytes.len() >= 4 && bytes[..4] != MAGIC {
+                ViewError::BadMagic
+            } else {
+                ViewError::Truncated
+            });
+        }
+
+        let (header, spans) = bytes.split_at(HEADER_LEN);
+        if header[..4] != MAGIC {
+            return Err(ViewError::BadMagic);
+        } else if header[4] != VERSION {
+            return Err(ViewError::UnsupportedVersion(header[4]));
+        } else if header[5] != T::SIGNED as u8
+                || usize::from(header[6]) != T::WIDTH {
+            return Err(ViewError::WrongDomain {
+                signed: header[5] != 0,
+                width: header[6],
+            });
+        }
+
+        let count = u64::from_le_slice(&header[8..]);
+        let available = (spans.len() / (2 * T::WIDTH)) as u64;
+        if count > available {
+            return Err(ViewError::Truncated);
+        } else if count < available
+                || spans.len() % (2 * T::WIDTH) != 0 {
+            return Err(ViewError::TrailingBytes);
+        }
+
+        let view = SpetView { spans, domain: PhantomData };
+        let mut previous_end: Option<T> = None;
+        for (index, span) in view.iter().enumerate() {
+            if span.start() > span.end() {
+                return Err(ViewError::Reversed { index });
+            } else if previous_end.is_some_and(|end| *span.start() < end) {
+                return Err(ViewError::Unsorted { index });
+            }
+            previous_end = Some(*span.end());
+        }
+
+        Ok(view)
+    }
+
+    pub fn len(&self) -> usize {
+        self.spans.len() / (2 * T::WIDTH)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<SimpleSpan<T>> {
+        if index < self.len() {
+            Some(self.span(index))
+        } else {
+            None
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'a, T> {
+        Iter { view: *self, front: 0, back: self.len() }
+    }
+
+    /**
+     * Copies the spans into a VecSpet.
+     */
+    pub fn to_vecspet(&self) -> VecSpet<SimpleSpan<T>> {
+        VecSpet { spans: self.iter().collect() }
+    }
+
+    /**
+     * Whether any span contains `point`. This is O(log N).
+     */
+    pub fn contains(&self, point: &T) -> bool {
+        let index = self.partition_point(|span| span.end() <= point);
+        self.get(index).is_some_and(|span| span.contains(point))
+    }
+
+    /**
+     * The spans that overlap `window`, as a view into the same bytes. Works
+     * like VecSpet::slice().
+     */
+    pub fn slice<W: Span<Domain = T>>(&self, window: &W) -> SpetView<'a, T> {
+        if window.start() >= window.end() {
+            return self.sub_view(0, 0);
+        }
+
+        let first = self.partition_point(|span| span.end() <= window.start());
+        let last = self.partition_point(|span| span.start() < window.end());
+        self.sub_view(first, last)
+    }
+
+    /**
+     * Trims the spet down to the parts within `window`. Works like
+     * VecSpet::clip().
+     */
+    pub fn clip<W: Span<Domain = T>>(&self, window: &W)
+            -> VecSpet<SimpleSpan<T>> {
+        VecSpet {
+            spans: self.slice(window).iter().map(|span| SimpleSpan::new(
+                *std::cmp::max(span.start(), window.start()),
+                *std::cmp::min(span.end(), window.end())))
+                .collect(),
+        }
+    }
+
+    pub fn union(&self, other: &SpetView<'_, T>) -> VecSpet<SimpleSpan<T>> {
+        VecSpet::from_sorted_iter(sorted_chain([self.iter(), other.iter()]))
+    }
+
+    pub fn intersection(&self, other: &SpetView<'_, T>)
+            -> VecSpet<SimpleSpan<T>> {
+        crate::overlapping::n_overlapping(2, [self.iter(), other.iter()])
+    }
+
+    /**
+     * Everything in this spet that isn't in `other`.
+     */
+    pub fn difference(&self, other: &SpetView<'_, T>)
+            -> VecSpet<SimpleSpan<T>> {
+        let mut difference = SpanBuilder::new();
+        VecSpet::<SimpleSpan<T>>::sweep(
+            vec![self.iter(), other.iter()],
+            |position, counts| {
+                difference.cover(position, counts[0] > 0 && counts[1] == 0);
+            });
+
+        difference.finish()
+    }
+
+    fn span(&self, index: usize) -> SimpleSpan<T> {
+        let offset = index * 2 * T::WIDTH;
+        let start = T::from_le_slice(&self.spans[offset..offset + T::WIDTH]);
+        let end = T::from_le_slice(
+            &self.spans[offset + T::WIDTH..offset + 2 * T::WIDTH]);
+        SimpleSpan::new(start, end)
+    }
+
+    fn sub_view(&self, first: usize, last: usize) -> SpetView<'a, T> {
+        let width = 2 * T::WIDTH;
+        SpetView {
+            spans: &self.spans[first * width..last * width],
+            domain: PhantomData,
+        }
+    }
+
+    // Index of the first span for which `is_before` is false. Like
+    // slice::partition_point(), `is_before` must be true for some prefix of
+    // the spans and false for the rest.
+    fn partition_point(&self, is_before: impl Fn(&SimpleSpan<T>) -> bool)
+            -> usize {
+        let mut low = 0;
+        let mut high = self.len();
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if is_before(&self.span(middle)) {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        low
+    }
+}
+
+
+impl<'a, T: FixedWidth + Debug> Debug for SpetView<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+
+/**
+ * Iterator over the spans of a SpetView, created by SpetView::iter().
+ */
+#[derive(Clone)]
+pub struct Iter<'a, T: FixedWidth> {
+    view: SpetView<'a, T>,
+    front: usize,
+    back: usize,
+}
+
+
+impl<'a, T: FixedWidth> Iterator for Iter<'a, T> {
+    type Item = SimpleSpan<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front < self.back {
+            self.front += 1;
+            Some(self.view.span(self.front - 1))
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.back - self.front, Some(self.back - self.front))
+    }
+}
+
+
+impl<'a, T: FixedWidth> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front < self.back {
+            self.back -= 1;
+            Some(self.view.span(self.back))
+        } else {
+            None
+        }
+    }
+}
+
+
+impl<'a, T: FixedWidth> ExactSizeIterator for Iter<'a, T> {}
+
+
+impl<'a, T: FixedWidth> IntoIterator for SpetView<'a, T> {
+    type Item = SimpleSpan<T>;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+
+impl<'a, T: FixedWidth> IntoIterator for &SpetView<'a, T> {
+    type Item = SimpleSpan<T>;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::view::{SpetView, ViewError, HEADER_LEN};
+    use crate::span::{SimpleSpan, CreatableSpan};
+    use crate::vecspet::VecSpet;
+
+    type SSpan = SimpleSpan<i64>;
+    type Spet = VecSpet<SSpan>;
+
+    fn spet(spans: &[(i64, i64)]) -> Spet {
+        Spet::from_sorted_iter(
+            spans.iter().map(|(start, end)| SSpan::new(*start, *end)))
+    }
+
+    fn encode(spet: &Spet) -> Vec<u8> {
+        let mut out = Vec::new();
+        spet.write_fixed_width_to(&mut out).unwrap();
+        out
+    }
+
+    fn example() -> Vec<u8> {
+        encode(&spet(&[(1, 3), (4, 5), (6, 9), (10, 12)]))
+    }
+
+    #[test]
+    fn layout() {
+        let bytes = encode(&spet(&[(1, 3)]));
+
+        assert_eq!(&bytes[..HEADER_LEN],
+                   b"SPFW\x01\x01\x08\x00\x01\x00\x00\x00\x00\x00\x00\x00");
+        assert_eq!(&bytes[HEADER_LEN..],
+                   b"\x01\x00\x00\x00\x00\x00\x00\x00\
+                     \x03\x00\x00\x00\x00\x00\x00\x00");
+    }
+
+    #[test]
+    fn round_trip() {
+        let original = spet(&[(-5, -2), (0, 1), (3, i64::MAX)]);
+        let bytes = encode(&original);
+
+        let view = SpetView::<i64>::new(&bytes).unwrap();
+        assert_eq!(view.len(), 3);
+        assert_eq!(view.get(1), Some(SSpan::new(0, 1)));
+        assert_eq!(view.get(3), None);
+        assert_eq!(view.to_vecspet(), original);
+        assert_eq!(view.iter().next_back(), Some(SSpan::new(3, i64::MAX)));
+    }
+
+    #[test]
+    fn contains() {
+        let bytes = example();
+        let view = SpetView::<i64>::new(&bytes).unwrap();
+
+        let contained: Vec<i64> = (0..13).filter(|p| view.contains(p))
+            .collect();
+        assert_eq!(contained, vec![1, 2, 4, 6, 7, 8, 10, 11]);
+    }
+
+    #[test]
+    fn slice() {
+        let bytes = example();
+        let view = SpetView::<i64>::new(&bytes).unwrap();
+
+        let sliced = view.slice(&SSpan::new(2, 7));
+        assert_eq!(sliced.to_vecspet(), spet(&[(1, 3), (4, 5), (6, 9)]));
+        assert!(view.slice(&SSpan::new(3, 4)).is_empty());
+        assert!(view.slice(&SSpan::new(2, 2)).is_empty());
+    }
+
+    #[test]
+    fn clip() {
+        let bytes = example();
+        let view = SpetView::<i64>::new(&bytes).unwrap();
+
+        assert_eq!(view.clip(&SSpan::new(2, 7)),
+                   spet(&[(2, 3), (4, 5), (6, 7)]));
+    }
+
+    #[test]
+    fn set_operations_match_vecspet() {
+        let a = spet(&[(1, 3), (4, 5), (6, 9), (10, 12)]);
+        let b = spet(&[(0, 2), (5, 7), (8, 11)]);
+        let (a_bytes, b_bytes) = (encode(&a), encode(&b));
+        let a_view = SpetView::<i64>::new(&a_bytes).unwrap();
+        let b_view = SpetView::<i64>::new(&b_bytes).unwrap();
+
+        assert_eq!(a_view.union(&b_view), a.union(&b));
+        assert_eq!(a_view.intersection(&b_view), a.intersection(&b));
+        assert_eq!(a_view.difference(&b_view), a.difference(&b));
+        assert_eq!(b_view.difference(&a_view), b.difference(&a));
+    }
+
+    #[test]
+    fn n_overlapping() {
+        let a = encode(&spet(&[(1, 5)]));
+        let b = encode(&spet(&[(3, 8)]));
+        let views = vec![
+            SpetView::<i64>::new(&a).unwrap(),
+            SpetView::<i64>::new(&b).unwrap(),
+        ];
+
+        let result: Spet = crate::overlapping::n_overlapping(2, views);
+        assert_eq!(result, spet(&[(3, 5)]));
+    }
+
+    #[test]
+    fn small_domains() {
+        let original = VecSpet::<SimpleSpan<u8>>::from_sorted_iter(
+            vec![SimpleSpan::new(1u8, 2), SimpleSpan::new(200, 255)]);
+        let mut bytes = Vec::new();
+        original.write_fixed_width_to(&mut bytes).unwrap();
+
+        assert_eq!(bytes.len(), HEADER_LEN + 4);
+        assert_eq!(SpetView::<u8>::new(&bytes).unwrap().to_vecspet(),
+                   original);
+    }
+
+    mod errors {
+        use crate::view::{SpetView, ViewError, HEADER_LEN};
+        use super::{encode, example, spet};
+
+        fn check(bytes: &[u8]) -> ViewError {
+            SpetView::<i64>::new(bytes).unwrap_err()
+        }
+
+        #[test]
+        fn truncated() {
+            let bytes = example();
+
+            assert_eq!(check(&bytes[..3]), ViewError::Truncated);
+            assert_eq!(check(&bytes[..HEADER_LEN - 1]), ViewError::Truncated);
+            assert_eq!(check(&bytes[..bytes.len() - 16]),
+                       ViewError::Truncated);
+            assert_eq!(check(&bytes[..bytes.len() - 1]),
+                       ViewError::Truncated);
+        }
+
+        #[test]
+        fn trailing_bytes() {
+            let mut bytes = example();
+            bytes.push(0);
+
+            assert_eq!(check(&bytes), ViewError::TrailingBytes);
+        }
+
+        #[test]
+        fn bad_magic() {
+            let mut bytes = example();
+            bytes[0] = b'X';
+
+            assert_eq!(check(&bytes), ViewError::BadMagic);
+            assert_eq!(check(b"nope"), ViewError::BadMagic);
+        }
+
+        #[test]
+        fn unsupported_version() {
+            let mut bytes = example();
+            bytes[4] = 9;
+
+            assert_eq!(check(&bytes), ViewError::UnsupportedVersion(9));
+        }
+
+        #[test]
+        fn wrong_domain() {
+            let bytes = example();
+
+            assert_eq!(SpetView::<u64>::new(&bytes).unwrap_err(),
+                       ViewError::WrongDomain { signed: true, width: 8 });
+            assert_eq!(SpetView::<i32>::new(&bytes).unwrap_err(),
+                       ViewError::WrongDomain { signed: true, width: 8 });
+        }
+
+        #[test]
+        fn reversed() {
+            let mut bytes = encode(&spet(&[(1, 3), (4, 5)]));
+            // Sets the second span's end to 2.
+            bytes[HEADER_LEN + 24] = 2;
+
+            assert_eq!(check(&bytes), ViewError::Reversed { index: 1 });
+        }
+
+        #[test]
+        fn unsorted() {
+            let mut bytes = encode(&spet(&[(1, 3), (4, 5)]));
+            // Sets the second span's start to 2.
+            bytes[HEADER_LEN + 16] = 2;
+
+            assert_eq!(check(&bytes), ViewError::Unsorted { index: 1 });
+        }
+    }
+
+    #[test]
+    fn error_display() {
+        assert_eq!(ViewError::WrongDomain { signed: false, width: 4 }
+                       .to_string(),
+                   "spet is over u32");
+    }
+}